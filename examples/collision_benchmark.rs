@@ -0,0 +1,133 @@
+/*
+ * 大量の弾がある状況で、総当たりとSpatialHashの当たり判定の速度を比較する
+ *
+ * cargo run --release --example collision_benchmark -- [弾の数] [フレーム数]
+ */
+use bevy::prelude::*;
+use bevy_sample_stg::hitbox::{CollisionLayer, Hitbox, SpatialHash};
+use bevy_sample_stg::play_area::PlayAreaDescriptor;
+use bevy_sample_stg::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use rand::prelude::*;
+use std::time::{Duration, Instant};
+
+const DEFAULT_BULLET_COUNT: usize = 5000;
+const DEFAULT_FRAMES: usize = 600;
+const PLAYER_SHOT_COUNT: usize = 60;
+const CELL_SIZE: f32 = 32.0;
+
+struct Body {
+    entity: Entity,
+    position: Vec3,
+    velocity: Vec3,
+    hitbox: Hitbox,
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let bullet_count = args
+        .next()
+        .map(|s| s.parse().expect("bullet count must be a number"))
+        .unwrap_or(DEFAULT_BULLET_COUNT);
+    let frames = args
+        .next()
+        .map(|s| s.parse().expect("frames must be a number"))
+        .unwrap_or(DEFAULT_FRAMES);
+
+    let play_area_width = WINDOW_WIDTH / 3. * 2.;
+    let play_area = PlayAreaDescriptor {
+        width: play_area_width,
+        height: WINDOW_HEIGHT,
+        origin: Vec3::new(-WINDOW_WIDTH / 2. + play_area_width / 2., 0., 0.),
+    };
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut bullets = spawn_bodies(&mut rng, &play_area, bullet_count, 0, Hitbox::circle(1.5));
+    let mut shots = spawn_bodies(
+        &mut rng,
+        &play_area,
+        PLAYER_SHOT_COUNT,
+        bullet_count as u32,
+        Hitbox::rect(Vec2::new(6., 15.)),
+    );
+
+    let mut spatial_hash = SpatialHash::new(&play_area, CELL_SIZE);
+    let mut brute_force_time = Duration::ZERO;
+    let mut spatial_hash_time = Duration::ZERO;
+    let mut brute_force_hits = 0;
+    let mut spatial_hash_hits = 0;
+
+    for _ in 0..frames {
+        move_bodies(&mut bullets, &play_area);
+        move_bodies(&mut shots, &play_area);
+
+        let start = Instant::now();
+        for shot in shots.iter() {
+            brute_force_hits += bullets
+                .iter()
+                .filter(|b| shot.hitbox.intersects(shot.position, &b.hitbox, b.position))
+                .count();
+        }
+        brute_force_time += start.elapsed();
+
+        // 毎フレームの作り直しも含めて計測する
+        let start = Instant::now();
+        spatial_hash.clear();
+        for b in bullets.iter() {
+            spatial_hash.insert(CollisionLayer::EnemyBullet, b.entity, b.position, b.hitbox);
+        }
+        for shot in shots.iter() {
+            spatial_hash_hits += spatial_hash
+                .query(CollisionLayer::EnemyBullet, shot.position, &shot.hitbox)
+                .count();
+        }
+        spatial_hash_time += start.elapsed();
+    }
+
+    assert_eq!(brute_force_hits, spatial_hash_hits);
+    println!(
+        "bullets: {}, shots: {}, frames: {}, hits: {}",
+        bullet_count, PLAYER_SHOT_COUNT, frames, brute_force_hits
+    );
+    println!(
+        "brute force : {:>10.3} ms/frame",
+        brute_force_time.as_secs_f64() * 1000. / frames as f64
+    );
+    println!(
+        "spatial hash: {:>10.3} ms/frame",
+        spatial_hash_time.as_secs_f64() * 1000. / frames as f64
+    );
+}
+
+fn spawn_bodies(
+    rng: &mut StdRng,
+    play_area: &PlayAreaDescriptor,
+    count: usize,
+    first_id: u32,
+    hitbox: Hitbox,
+) -> Vec<Body> {
+    (0..count)
+        .map(|i| Body {
+            entity: Entity::from_raw(first_id + i as u32),
+            position: Vec3::new(
+                rng.gen_range(play_area.min_x()..play_area.max_x()),
+                rng.gen_range(play_area.min_y()..play_area.max_y()),
+                0.,
+            ),
+            velocity: Vec3::new(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0), 0.),
+            hitbox,
+        })
+        .collect()
+}
+
+// プレイエリアの端で跳ね返らせて、弾の密度を保つ
+fn move_bodies(bodies: &mut [Body], play_area: &PlayAreaDescriptor) {
+    for body in bodies.iter_mut() {
+        body.position += body.velocity;
+        if body.position.x < play_area.min_x() || body.position.x > play_area.max_x() {
+            body.velocity.x = -body.velocity.x;
+        }
+        if body.position.y < play_area.min_y() || body.position.y > play_area.max_y() {
+            body.velocity.y = -body.velocity.y;
+        }
+    }
+}
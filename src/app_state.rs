@@ -2,6 +2,13 @@
 pub enum AppState {
     Menu,
     InGame,
+    Paused,
+    Continue,
     GameOver,
     Ending,
+    BarragePreview,
+    HighScore,
+    PracticeMenu,
+    Replays,
+    Options,
 }
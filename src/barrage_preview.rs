@@ -0,0 +1,293 @@
+use crate::app_state::AppState;
+use crate::in_game::enemy::{
+    barrage_keys, BarrageServer, BarrageStarter, Bullet, ReleaseBullet, ENEMY_SIZE,
+};
+use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::menu_navigation::{
+    MenuCancelEvent, MenuChangeEvent, MenuMoveActionLock, MenuSelectEvent,
+};
+use crate::menu_widget::{
+    changed_items, selected_items, set_menu_item_label, spawn_sized_menu, MenuItemSize,
+};
+use crate::WINDOW_WIDTH;
+use bevy::prelude::*;
+
+const EMITTER_MARGIN_TOP: f32 = 100.0;
+const RANK_STEP: f64 = 0.1;
+
+pub struct BarragePreviewPlugin;
+
+impl Plugin for BarragePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::BarragePreview).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::BarragePreview)
+                    .with_system(preview_menu_system)
+                    .with_system(restart_barrage_system.after(preview_menu_system))
+                    .with_system(display_preview_system.after(preview_menu_system)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::BarragePreview).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+struct BarragePreview {
+    keys: Vec<String>,
+    index: usize,
+    rank: f64,
+    // 次のフレームで弾幕を撃ち直す
+    needs_restart: bool,
+    // 弾幕を始められなかった理由。引数が必要な弾幕は引数なしでは撃てない
+    error: Option<String>,
+}
+
+impl BarragePreview {
+    fn current_key(&self) -> Option<&str> {
+        self.keys.get(self.index).map(|k| k.as_str())
+    }
+
+    fn cycle(&mut self, diff: isize) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let len = self.keys.len() as isize;
+        self.index = (self.index as isize + diff).rem_euclid(len) as usize;
+        self.needs_restart = true;
+    }
+
+    fn change_rank(&mut self, diff: f64) {
+        // 小数の誤差が表示に出ないように丸める
+        self.rank = ((self.rank + diff).clamp(0., 1.) * 10.).round() / 10.;
+        self.needs_restart = true;
+    }
+
+    fn label(&self, item: PreviewMenuItem) -> String {
+        match item {
+            PreviewMenuItem::Pattern => format!("< {} >", self.current_key().unwrap_or("-")),
+            PreviewMenuItem::Rank => format!("Rank < {:.1} >", self.rank),
+            PreviewMenuItem::Restart => "Restart".to_string(),
+            PreviewMenuItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct PreviewEntity;
+
+#[derive(Component)]
+struct PreviewEmitter;
+
+#[derive(Component)]
+struct PreviewText;
+
+// 値の項目は左右で変え、選ぶと一つ進める
+#[derive(Component, Clone, Copy)]
+enum PreviewMenuItem {
+    Pattern,
+    Rank,
+    Restart,
+    Back,
+}
+
+/*
+ * System
+ */
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    play_area: Res<PlayAreaDescriptor>,
+) {
+    let preview = BarragePreview {
+        keys: barrage_keys(),
+        index: 0,
+        rank: 0.5,
+        needs_restart: true,
+        error: None,
+    };
+    // 移動の操作は自機を動かすのに使う
+    commands.insert_resource(MenuMoveActionLock);
+
+    // 弾幕の発射元になるダミーの敵
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(
+                    play_area.origin.x,
+                    play_area.max_y() - EMITTER_MARGIN_TOP,
+                    0.,
+                ),
+                scale: Vec3::new(ENEMY_SIZE, ENEMY_SIZE, ENEMY_SIZE),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1.0, 1.0)),
+                ..Default::default()
+            },
+            texture: asset_server.load("images/purple_alien.png"),
+            ..Default::default()
+        })
+        .insert(PreviewEmitter)
+        .insert(PreviewEntity);
+
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(20.),
+                    left: Val::Px(WINDOW_WIDTH / 3. * 2. + 15.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 18.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(PreviewText);
+        })
+        .insert(PreviewEntity)
+        .id();
+    let items: Vec<(PreviewMenuItem, String)> = [
+        PreviewMenuItem::Pattern,
+        PreviewMenuItem::Rank,
+        PreviewMenuItem::Restart,
+        PreviewMenuItem::Back,
+    ]
+    .iter()
+    .map(|item| (*item, preview.label(*item)))
+    .collect();
+    // 右側の空いているところに収まる大きさにする
+    let menu_entity = spawn_sized_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        MenuItemSize {
+            width: WINDOW_WIDTH / 3. - 30.,
+            height: 28.0,
+            margin: 3.0,
+            font_size: 16.0,
+        },
+        &items,
+    );
+    commands.entity(entity).add_child(menu_entity);
+    commands.insert_resource(preview);
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<PreviewEntity>>) {
+    commands.remove_resource::<BarragePreview>();
+    commands.remove_resource::<MenuMoveActionLock>();
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn preview_menu_system(
+    mut preview: ResMut<BarragePreview>,
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut change_events: EventReader<MenuChangeEvent>,
+    mut cancel_events: EventReader<MenuCancelEvent>,
+    item_query: Query<&PreviewMenuItem>,
+) {
+    for (_, item, diff) in changed_items(&mut change_events, &item_query) {
+        match item {
+            PreviewMenuItem::Pattern => preview.cycle(diff),
+            PreviewMenuItem::Rank => preview.change_rank(RANK_STEP * diff as f64),
+            _ => {}
+        }
+    }
+    for (_, item) in selected_items(&mut select_events, &item_query) {
+        match item {
+            PreviewMenuItem::Pattern => preview.cycle(1),
+            PreviewMenuItem::Rank => preview.change_rank(RANK_STEP),
+            PreviewMenuItem::Restart => preview.needs_restart = true,
+            PreviewMenuItem::Back => {
+                let _ = state.set(AppState::Menu);
+            }
+        }
+    }
+    if cancel_events.iter().count() > 0 {
+        let _ = state.set(AppState::Menu);
+    }
+}
+
+// 操作されたとき、または弾幕が終わって弾が無くなったときに撃ち直す
+fn restart_barrage_system(
+    mut commands: Commands,
+    barrage_server: Res<BarrageServer>,
+    mut preview: ResMut<BarragePreview>,
+    emitter_query: Query<(Entity, &Transform), With<PreviewEmitter>>,
+    bullet_query: Query<(Entity, &Bullet)>,
+) {
+    // プールで待機中の弾は数えない
+    let mut bullets = bullet_query.iter().filter(|(_, bullet)| !bullet.released);
+    if !preview.needs_restart && (bullets.next().is_some() || preview.error.is_some()) {
+        return;
+    }
+
+    for (e, bullet) in bullet_query.iter() {
+        if !bullet.released {
+            commands.add(ReleaseBullet::new(e, bullet));
+        }
+    }
+    let mut error = None;
+    if let Some(key) = preview.current_key() {
+        let (emitter_entity, emitter_transform) = emitter_query.single();
+        if let Err(e) = commands.start_barrage(
+            emitter_transform,
+            &barrage_server,
+            key,
+            preview.rank,
+            Some(emitter_entity),
+        ) {
+            error = Some(e.to_string());
+        }
+    }
+    preview.error = error;
+    preview.needs_restart = false;
+}
+
+fn display_preview_system(
+    preview: Res<BarragePreview>,
+    item_query: Query<(&PreviewMenuItem, &Children)>,
+    preview_text_query: Query<Entity, With<PreviewText>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !preview.is_changed() {
+        return;
+    }
+    for (item, children) in item_query.iter() {
+        set_menu_item_label(children, &mut text_query, &preview.label(*item));
+    }
+
+    let mut lines = vec!["BARRAGE PREVIEW".to_string(), "".to_string()];
+    if let Some(error) = &preview.error {
+        lines.push(error.clone());
+        lines.push("".to_string());
+    }
+    lines.push("Left/Right: Change".to_string());
+    lines.push("WASD: Move".to_string());
+
+    let mut text = text_query.get_mut(preview_text_query.single()).unwrap();
+    text.sections[0].value = lines.join("\n");
+}
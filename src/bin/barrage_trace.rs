@@ -0,0 +1,135 @@
+/*
+ * data/barrageの弾幕を画面なしで動かし、フレームごとの弾の位置・向き・速度を出力する
+ *
+ * cargo run --bin barrage_trace -- <弾幕名> [--frames N] [--origin X,Y] [--target X,Y]
+ *     [--rank R] [--format csv|json] [--seed N] [--output FILE]
+ */
+use bevy::prelude::*;
+use bevy_sample_stg::barrage_simulator::{BarrageSimulator, BulletSnapshot};
+use bevy_sample_stg::play_area::PlayAreaDescriptor;
+use std::fs::File;
+use std::io::{self, Write};
+
+const DEFAULT_FRAMES: i128 = 600;
+// 省略時の発射位置と自機の位置の、プレイエリアの上端・下端からの距離
+const DEFAULT_MARGIN: f32 = 100.;
+
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Options {
+    barrage_type: String,
+    frames: i128,
+    origin: Vec3,
+    target: Vec3,
+    rank: f64,
+    format: Format,
+    seed: u64,
+    output: Option<String>,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "usage: barrage_trace <barrage> [--frames N] [--origin X,Y] [--target X,Y] [--rank R] \
+                 [--format csv|json] [--seed N] [--output FILE]"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> Result<(), anyhow::Error> {
+    let mut simulator = BarrageSimulator::new(
+        &options.barrage_type,
+        options.origin,
+        options.target,
+        options.rank,
+        options.seed,
+    )?;
+    let mut snapshots: Vec<BulletSnapshot> = Vec::new();
+    for _ in 0..options.frames {
+        simulator.step();
+        snapshots.extend(simulator.snapshot());
+    }
+
+    let writer: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    match options.format {
+        Format::Csv => {
+            let mut wtr = csv::Writer::from_writer(writer);
+            for snapshot in snapshots {
+                wtr.serialize(snapshot)?;
+            }
+            wtr.flush()?;
+        }
+        Format::Json => {
+            let mut writer = writer;
+            serde_json::to_writer_pretty(&mut writer, &snapshots)?;
+            writeln!(writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, anyhow::Error> {
+    let barrage_type = args
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("barrage name is required"))?;
+    let play_area = PlayAreaDescriptor::default();
+    let mut options = Options {
+        barrage_type,
+        frames: DEFAULT_FRAMES,
+        origin: Vec3::new(play_area.origin.x, play_area.max_y() - DEFAULT_MARGIN, 0.),
+        target: Vec3::new(play_area.origin.x, play_area.min_y() + DEFAULT_MARGIN, 0.),
+        rank: 0.5,
+        format: Format::Csv,
+        seed: 0,
+        output: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--frames" => options.frames = value.parse()?,
+            "--origin" => options.origin = parse_position(&value)?,
+            "--target" => options.target = parse_position(&value)?,
+            "--rank" => options.rank = value.parse()?,
+            "--format" => {
+                options.format = match value.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(anyhow::anyhow!("unsupported format: {}", value)),
+                }
+            }
+            "--seed" => options.seed = value.parse()?,
+            "--output" => options.output = Some(value),
+            _ => return Err(anyhow::anyhow!("unknown option: {}", flag)),
+        }
+    }
+
+    Ok(options)
+}
+
+// "x,y"形式の座標
+fn parse_position(value: &str) -> Result<Vec3, anyhow::Error> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| anyhow::anyhow!("position must be X,Y: {}", value))?;
+    Ok(Vec3::new(x.trim().parse()?, y.trim().parse()?, 0.))
+}
@@ -0,0 +1,203 @@
+use crate::app_state::AppState;
+use crate::high_score::ScoreCategory;
+use crate::in_game::enemy::BarrageRank;
+use crate::in_game::game_frame::GameFrame;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path;
+
+const DEFAULT_DIFFICULTY: &str = "Normal";
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        // 読み込めなければ、組み込みのNormalだけで遊べるようにする
+        let difficulties = Difficulties::load_file("data/difficulty.csv").unwrap_or_else(|e| {
+            warn!("Failed to load difficulties: {}", e);
+            Difficulties::default()
+        });
+        app.insert_resource(difficulties)
+            .add_system(sync_score_category_system)
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame).with_system(update_barrage_rank_system),
+            );
+    }
+}
+
+/*
+ * Resource
+ */
+pub struct Difficulty {
+    pub name: String,
+    pub player_stock: i32,
+    pub player_bomb: i32,
+    // 弾幕のランクはゲームの進行に合わせてrank_minからrank_maxまで上がる
+    pub rank_min: f64,
+    pub rank_max: f64,
+    // ランクがrank_minからrank_maxまで上がりきるフレーム数
+    pub rank_rise_frames: i128,
+    pub enemy_hp_rate: f32,
+    pub boss_hp_rate: f32,
+    // 弾幕名(引数を除く)から、差し替える弾幕の呼び出しへ
+    barrage_overrides: HashMap<String, String>,
+}
+
+impl Difficulty {
+    // 差し替えが無ければそのまま返す
+    pub fn barrage_type<'a>(&'a self, barrage_type: &'a str) -> &'a str {
+        let name = barrage_type.split('(').next().unwrap_or("").trim();
+        self.barrage_overrides
+            .get(name)
+            .map(|s| s.as_str())
+            .unwrap_or(barrage_type)
+    }
+
+    pub fn enemy_hp(&self, hp: i32) -> i32 {
+        scale_hp(hp, self.enemy_hp_rate)
+    }
+
+    pub fn boss_hp(&self, hp: i32) -> i32 {
+        scale_hp(hp, self.boss_hp_rate)
+    }
+
+    fn rank(&self, frame: i128) -> f64 {
+        let progress = (frame as f64 / self.rank_rise_frames.max(1) as f64).clamp(0., 1.);
+        self.rank_min + (self.rank_max - self.rank_min) * progress
+    }
+}
+
+pub struct Difficulties {
+    levels: Vec<Difficulty>,
+    selected: usize,
+}
+
+impl Default for Difficulties {
+    fn default() -> Self {
+        Self {
+            levels: vec![Difficulty {
+                name: DEFAULT_DIFFICULTY.to_string(),
+                player_stock: 3,
+                player_bomb: 3,
+                rank_min: 0.4,
+                rank_max: 0.6,
+                rank_rise_frames: 600,
+                enemy_hp_rate: 1.0,
+                boss_hp_rate: 1.0,
+                barrage_overrides: HashMap::new(),
+            }],
+            selected: 0,
+        }
+    }
+}
+
+impl Difficulties {
+    pub fn load_file<P: AsRef<path::Path>>(file_path: P) -> Result<Self, anyhow::Error> {
+        let file = File::open(file_path)?;
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(true)
+            .from_reader(file);
+        let mut levels = vec![];
+        for result in rdr.records() {
+            let record = result?;
+            // 差し替える弾幕(省略可、`triple=nway(5, 10, 2)`のように指定し、複数の場合は`;`区切り)
+            let barrage_overrides = record
+                .get(8)
+                .unwrap_or("")
+                .split(';')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    s.split_once('=')
+                        .map(|(from, to)| (from.trim().to_string(), to.trim().to_string()))
+                        .ok_or_else(|| anyhow::anyhow!("Invalid barrage override: {}", s))
+                })
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            levels.push(Difficulty {
+                name: record[0].to_string(),
+                player_stock: record[1].parse::<i32>()?,
+                player_bomb: record[2].parse::<i32>()?,
+                rank_min: record[3].parse::<f64>()?,
+                rank_max: record[4].parse::<f64>()?,
+                rank_rise_frames: record[5].parse::<i128>()?,
+                enemy_hp_rate: record[6].parse::<f32>()?,
+                boss_hp_rate: record[7].parse::<f32>()?,
+                barrage_overrides,
+            });
+        }
+        if levels.is_empty() {
+            return Err(anyhow::anyhow!("No difficulty is defined"));
+        }
+
+        let selected = levels
+            .iter()
+            .position(|d| d.name == DEFAULT_DIFFICULTY)
+            .unwrap_or(0);
+        Ok(Self { levels, selected })
+    }
+
+    pub fn current(&self) -> &Difficulty {
+        &self.levels[self.selected]
+    }
+
+    pub fn cycle(&mut self, diff: isize) {
+        let len = self.levels.len() as isize;
+        self.selected = (self.selected as isize + diff).rem_euclid(len) as usize;
+    }
+}
+
+/*
+ * System
+ */
+// 選んだ難易度ごとにハイスコアを記録する。リプレイへの難易度の記録は、リプレイ機能を作るときまで見送る
+fn sync_score_category_system(
+    difficulties: Res<Difficulties>,
+    mut category: ResMut<ScoreCategory>,
+) {
+    if difficulties.is_changed() {
+        category.difficulty = difficulties.current().name.clone();
+    }
+}
+
+fn update_barrage_rank_system(
+    game_frame: Res<GameFrame>,
+    difficulties: Res<Difficulties>,
+    mut barrage_rank: ResMut<BarrageRank>,
+) {
+    if game_frame.is_changed() {
+        barrage_rank.0 = difficulties.current().rank(game_frame.0);
+    }
+}
+
+/*
+ * Utils
+ */
+fn scale_hp(hp: i32, rate: f32) -> i32 {
+    ((hp as f32 * rate).round() as i32).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_rises_over_configured_frames() {
+        let difficulties = Difficulties::load_file("data/difficulty.csv").unwrap();
+        let normal = difficulties.current();
+        assert_eq!(normal.name, DEFAULT_DIFFICULTY);
+        assert_eq!(normal.rank(0), normal.rank_min);
+        let middle = normal.rank(normal.rank_rise_frames / 2);
+        assert!((middle - (normal.rank_min + normal.rank_max) / 2.).abs() < 1e-9);
+        assert_eq!(normal.rank(normal.rank_rise_frames * 2), normal.rank_max);
+    }
+
+    #[test]
+    fn builtin_normal_is_used_without_file() {
+        assert!(Difficulties::load_file("data/missing_difficulty.csv").is_err());
+        let difficulties = Difficulties::default();
+        assert_eq!(difficulties.current().name, DEFAULT_DIFFICULTY);
+        assert_eq!(difficulties.current().barrage_type("triple"), "triple");
+    }
+}
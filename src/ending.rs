@@ -1,11 +1,12 @@
-use crate::{app_state::AppState, in_game::scoreboard::Score};
+use crate::{
+    app_state::AppState,
+    in_game::scoreboard::Score,
+    menu_navigation::MenuSelectEvent,
+    menu_widget::{centered_menu_style, selected_items, spawn_menu},
+};
 
 use bevy::prelude::*;
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
-
 pub struct EndingPlugin;
 
 impl Plugin for EndingPlugin {
@@ -25,7 +26,13 @@ struct TitleData {
 }
 
 struct MenuData {
-    button_entity: Entity,
+    menu_entity: Entity,
+}
+
+#[derive(Component, Clone, Copy)]
+enum EndingMenuItem {
+    PlayAgain,
+    ReturnToTitle,
 }
 
 #[derive(Component)]
@@ -107,64 +114,36 @@ fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>, score: Re
 }
 
 fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let button_entity = commands
-        .spawn_bundle(ButtonBundle {
-            style: Style {
-                size: Size::new(Val::Px(300.0), Val::Px(65.0)),
-                // center button
-                margin: UiRect::all(Val::Auto),
-                // horizontally center child text
-                justify_content: JustifyContent::Center,
-                // vertically center child text
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            color: NORMAL_BUTTON.into(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::from_section(
-                    "Play Again",
-                    TextStyle {
-                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
-                        font_size: 40.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                ),
-                ..Default::default()
-            });
-        })
-        .id();
-    commands.insert_resource(MenuData { button_entity });
+    let menu_entity = spawn_menu(
+        &mut commands,
+        &asset_server,
+        centered_menu_style(),
+        &[
+            (EndingMenuItem::PlayAgain, "Play Again".to_string()),
+            (EndingMenuItem::ReturnToTitle, "Return to title".to_string()),
+        ],
+    );
+    commands.insert_resource(MenuData { menu_entity });
 }
 
 fn menu_system(
     mut state: ResMut<State<AppState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut select_events: EventReader<MenuSelectEvent>,
+    item_query: Query<&EndingMenuItem>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => {
-                *color = PRESSED_BUTTON.into();
-                state.set(AppState::InGame).unwrap();
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-            }
-        }
+    for (_, item) in selected_items(&mut select_events, &item_query) {
+        let next_state = match item {
+            EndingMenuItem::PlayAgain => AppState::InGame,
+            EndingMenuItem::ReturnToTitle => AppState::Menu,
+        };
+        // 同じフレームで複数選ばれたときは先に受け付けた方を優先する
+        let _ = state.set(next_state);
     }
 }
 
 fn cleanup(mut commands: Commands, title_data: Res<TitleData>, menu_data: Res<MenuData>) {
     commands.entity(title_data.title_entity).despawn_recursive();
-    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.menu_entity).despawn_recursive();
     commands.remove_resource::<Score>();
 }
 
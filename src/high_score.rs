@@ -0,0 +1,542 @@
+use crate::app_state::AppState;
+use crate::in_game::scoreboard::Score;
+use crate::input_action::InputAction;
+use crate::menu_navigation::{
+    MenuCancelEvent, MenuChangeEvent, MenuNavigationLock, MenuSelectEvent,
+};
+use crate::menu_widget::{changed_items, selected_items, set_menu_item_label, spawn_menu};
+use crate::practice::Practice;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const HIGH_SCORE_FILE: &str = "high_scores.json";
+const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LENGTH: usize = 8;
+const DEFAULT_NAME: &str = "PLAYER";
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = HighScores::load().unwrap_or_else(|e| {
+            warn!("Failed to load high scores: {}", e);
+            HighScores::default()
+        });
+        app.insert_resource(high_scores)
+            .init_resource::<ScoreCategory>()
+            .add_system_set(SystemSet::on_enter(AppState::HighScore).with_system(setup_view))
+            .add_system_set(
+                SystemSet::on_update(AppState::HighScore)
+                    .with_system(control_view_system)
+                    .with_system(display_view_system.after(control_view_system)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::HighScore).with_system(cleanup_view));
+        // ゲームオーバーとクリアの画面で、ランクインしていれば名前を入力する
+        for state in [AppState::GameOver, AppState::Ending] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup_name_entry))
+                .add_system_set(SystemSet::on_update(state.clone()).with_system(name_entry_system))
+                .add_system_set(SystemSet::on_exit(state).with_system(cleanup_name_entry));
+        }
+    }
+}
+
+/*
+ * Resource
+ */
+// ハイスコアを分けて記録する単位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreCategory {
+    pub difficulty: String,
+    pub mode: String,
+}
+
+impl Default for ScoreCategory {
+    fn default() -> Self {
+        Self {
+            difficulty: "Normal".to_string(),
+            mode: "Stage".to_string(),
+        }
+    }
+}
+
+impl ScoreCategory {
+    fn key(&self) -> String {
+        format!("{}/{}", self.difficulty, self.mode)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: i128,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    // ScoreCategory::keyごとに、スコアの高い順
+    tables: BTreeMap<String, Vec<HighScoreEntry>>,
+}
+
+impl HighScores {
+    pub fn load() -> Result<Self, anyhow::Error> {
+        storage::load_json(&storage::save_dir(), HIGH_SCORE_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        storage::save_json(&storage::save_dir(), HIGH_SCORE_FILE, self)
+    }
+
+    pub fn entries(&self, category: &ScoreCategory) -> &[HighScoreEntry] {
+        self.tables
+            .get(&category.key())
+            .map(|entries| entries.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn top_score(&self, category: &ScoreCategory) -> i128 {
+        self.entries(category)
+            .first()
+            .map(|entry| entry.score)
+            .unwrap_or(0)
+    }
+
+    // ランクインする場合は順位(0始まり)を返す。同点は先に登録したものが上
+    pub fn rank_of(&self, category: &ScoreCategory, score: i128) -> Option<usize> {
+        if score <= 0 {
+            return None;
+        }
+        let entries = self.entries(category);
+        let rank = entries
+            .iter()
+            .position(|entry| score > entry.score)
+            .unwrap_or(entries.len());
+        (rank < MAX_ENTRIES).then_some(rank)
+    }
+
+    pub fn insert(&mut self, category: &ScoreCategory, entry: HighScoreEntry) {
+        let rank = match self.rank_of(category, entry.score) {
+            Some(rank) => rank,
+            None => return,
+        };
+        let entries = self.tables.entry(category.key()).or_default();
+        entries.insert(rank, entry);
+        entries.truncate(MAX_ENTRIES);
+    }
+
+    // 記録のあるカテゴリのキー
+    fn keys(&self) -> Vec<String> {
+        self.tables.keys().cloned().collect()
+    }
+
+    fn entries_by_key(&self, key: &str) -> &[HighScoreEntry] {
+        self.tables
+            .get(key)
+            .map(|entries| entries.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+struct NameEntry {
+    category: ScoreCategory,
+    score: i128,
+    rank: usize,
+    name: String,
+    registered: bool,
+}
+
+impl NameEntry {
+    fn register(&mut self, high_scores: &mut HighScores) {
+        if self.registered {
+            return;
+        }
+        let name = if self.name.is_empty() {
+            DEFAULT_NAME.to_string()
+        } else {
+            self.name.clone()
+        };
+        high_scores.insert(
+            &self.category,
+            HighScoreEntry {
+                name,
+                score: self.score,
+            },
+        );
+        if let Err(e) = high_scores.save() {
+            warn!("Failed to save high scores: {}", e);
+        }
+        self.registered = true;
+    }
+}
+
+struct HighScoreView {
+    keys: Vec<String>,
+    index: usize,
+}
+
+impl HighScoreView {
+    fn key(&self) -> &str {
+        &self.keys[self.index]
+    }
+
+    fn cycle(&mut self, diff: isize) {
+        let len = self.keys.len() as isize;
+        self.index = (self.index as isize + diff).rem_euclid(len) as usize;
+    }
+}
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct NameEntryText;
+
+#[derive(Component)]
+struct HighScoreViewText;
+
+#[derive(Component, Clone, Copy)]
+enum HighScoreViewItem {
+    // 左右か選ぶと記録を見るカテゴリを切り替える
+    Category,
+    Back,
+}
+
+struct NameEntryData {
+    entity: Entity,
+}
+
+struct HighScoreViewData {
+    entity: Entity,
+}
+
+/*
+ * System
+ */
+fn setup_name_entry(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    category: Res<ScoreCategory>,
+    score: Res<Score>,
+    practice: Option<Res<Practice>>,
+) {
+    // 練習モードのスコアは記録しない
+    if practice.is_some() {
+        return;
+    }
+    let rank = match high_scores.rank_of(&category, score.score) {
+        Some(rank) => rank,
+        None => return,
+    };
+    // 名前の入力中は、文字のキーでメニューが動かないようにする
+    commands.insert_resource(MenuNavigationLock);
+    commands.insert_resource(NameEntry {
+        category: category.clone(),
+        score: score.score,
+        rank,
+        name: String::new(),
+        registered: false,
+    });
+
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(60.),
+                    left: Val::Px(0.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1., 1., 0.),
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    ..Default::default()
+                })
+                .insert(NameEntryText);
+        })
+        .id();
+    commands.insert_resource(NameEntryData { entity });
+}
+
+// 画面を抜けるときに確定していなければ、その時点の名前で登録する
+fn cleanup_name_entry(
+    mut commands: Commands,
+    name_entry: Option<ResMut<NameEntry>>,
+    data: Option<Res<NameEntryData>>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if let Some(mut name_entry) = name_entry {
+        name_entry.register(&mut high_scores);
+        commands.remove_resource::<NameEntry>();
+        commands.remove_resource::<MenuNavigationLock>();
+    }
+    if let Some(data) = data {
+        commands.entity(data.entity).despawn_recursive();
+        commands.remove_resource::<NameEntryData>();
+    }
+}
+
+fn name_entry_system(
+    mut commands: Commands,
+    mut action_input: ResMut<Input<InputAction>>,
+    mut char_events: EventReader<ReceivedCharacter>,
+    name_entry: Option<ResMut<NameEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    mut query: Query<&mut Text, With<NameEntryText>>,
+) {
+    let mut name_entry = match name_entry {
+        Some(name_entry) => name_entry,
+        None => return,
+    };
+    // プレイ中に押されていたキーは入力しない
+    if name_entry.is_added() {
+        for _ in char_events.iter() {}
+    } else if !name_entry.registered {
+        for event in char_events.iter() {
+            let c = event.char.to_ascii_uppercase();
+            // BackSpaceは環境によってBSかDELの文字で届く
+            if c == '\u{8}' || c == '\u{7f}' {
+                name_entry.name.pop();
+            } else if (c.is_ascii_alphanumeric() || c == ' ' || c == '.' || c == '-')
+                && name_entry.name.len() < MAX_NAME_LENGTH
+            {
+                name_entry.name.push(c);
+            }
+        }
+        // 決定したキーでそのままメニューが選ばれないように消しておく
+        if action_input.clear_just_pressed(InputAction::Confirm) {
+            name_entry.register(&mut high_scores);
+            commands.remove_resource::<MenuNavigationLock>();
+        }
+    }
+
+    if !name_entry.is_changed() {
+        return;
+    }
+    let mut text = query.single_mut();
+    text.sections[0].value = if name_entry.registered {
+        format!("Registered as No.{}!", name_entry.rank + 1)
+    } else {
+        format!(
+            "NEW RECORD! No.{}\nName: {}_\nEnter: OK",
+            name_entry.rank + 1,
+            name_entry.name
+        )
+    };
+}
+
+fn setup_view(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    category: Res<ScoreCategory>,
+) {
+    // 記録が無くても今のカテゴリは表示する
+    let mut keys = high_scores.keys();
+    if !keys.contains(&category.key()) {
+        keys.push(category.key());
+        keys.sort();
+    }
+    let index = keys.iter().position(|k| *k == category.key()).unwrap();
+    commands.insert_resource(HighScoreView { keys, index });
+
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1., 1., 1.),
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(HighScoreViewText);
+        })
+        .id();
+    let menu_entity = spawn_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        &[
+            (HighScoreViewItem::Category, category_label(&category.key())),
+            (HighScoreViewItem::Back, "Back".to_string()),
+        ],
+    );
+    commands.entity(entity).add_child(menu_entity);
+    commands.insert_resource(HighScoreViewData { entity });
+}
+
+fn cleanup_view(mut commands: Commands, data: Res<HighScoreViewData>) {
+    commands.entity(data.entity).despawn_recursive();
+    commands.remove_resource::<HighScoreViewData>();
+    commands.remove_resource::<HighScoreView>();
+}
+
+fn control_view_system(
+    mut view: ResMut<HighScoreView>,
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut change_events: EventReader<MenuChangeEvent>,
+    mut cancel_events: EventReader<MenuCancelEvent>,
+    item_query: Query<&HighScoreViewItem>,
+) {
+    for (_, item, diff) in changed_items(&mut change_events, &item_query) {
+        if let HighScoreViewItem::Category = item {
+            view.cycle(diff);
+        }
+    }
+    for (_, item) in selected_items(&mut select_events, &item_query) {
+        match item {
+            HighScoreViewItem::Category => view.cycle(1),
+            HighScoreViewItem::Back => {
+                let _ = state.set(AppState::Menu);
+            }
+        }
+    }
+    if cancel_events.iter().count() > 0 {
+        let _ = state.set(AppState::Menu);
+    }
+}
+
+fn display_view_system(
+    high_scores: Res<HighScores>,
+    view: Res<HighScoreView>,
+    item_query: Query<(&HighScoreViewItem, &Children)>,
+    view_text_query: Query<Entity, With<HighScoreViewText>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !view.is_changed() {
+        return;
+    }
+    let key = view.key();
+    for (item, children) in item_query.iter() {
+        if let HighScoreViewItem::Category = item {
+            set_menu_item_label(children, &mut text_query, &category_label(key));
+        }
+    }
+
+    let mut lines = vec!["HIGH SCORES".to_string(), "".to_string()];
+    let entries = high_scores.entries_by_key(key);
+    for rank in 0..MAX_ENTRIES {
+        lines.push(match entries.get(rank) {
+            Some(entry) => format!(
+                "{:>2}. {:<width$} {:>10}",
+                rank + 1,
+                entry.name,
+                entry.score,
+                width = MAX_NAME_LENGTH
+            ),
+            None => format!(
+                "{:>2}. {:<width$} {:>10}",
+                rank + 1,
+                "-",
+                "-",
+                width = MAX_NAME_LENGTH
+            ),
+        });
+    }
+
+    let mut text = text_query.get_mut(view_text_query.single()).unwrap();
+    text.sections[0].value = lines.join("\n");
+}
+
+/*
+ * Utils
+ */
+fn category_label(key: &str) -> String {
+    format!("< {} >", key.replace('/', " / "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+    use bevy::window::WindowId;
+
+    fn send_chars(world: &mut World, chars: &str) {
+        for c in chars.chars() {
+            world.send_event(ReceivedCharacter {
+                id: WindowId::primary(),
+                char: c,
+            });
+        }
+    }
+
+    #[test]
+    fn enters_name_and_registers_by_confirm() {
+        std::env::set_var("STG_SAVE_DIR", std::env::temp_dir().join("stg_name_entry"));
+        let mut world = World::new();
+        world.insert_resource(Input::<InputAction>::default());
+        world.init_resource::<Events<ReceivedCharacter>>();
+        world.insert_resource(HighScores::default());
+        world.insert_resource(NameEntry {
+            category: ScoreCategory::default(),
+            score: 100,
+            rank: 0,
+            name: String::new(),
+            registered: false,
+        });
+        world
+            .spawn()
+            .insert(Text::from_section("", TextStyle::default()))
+            .insert(NameEntryText);
+        let mut stage = SystemStage::single_threaded().with_system(name_entry_system);
+        // 画面に入った最初のフレームの文字は、プレイ中に押していたキーなので入力しない
+        send_chars(&mut world, "z");
+        stage.run(&mut world);
+
+        send_chars(&mut world, "ab\u{8}c");
+        stage.run(&mut world);
+        assert_eq!(world.resource::<NameEntry>().name, "AC");
+
+        world
+            .resource_mut::<Input<InputAction>>()
+            .press(InputAction::Confirm);
+        stage.run(&mut world);
+        assert!(world.resource::<NameEntry>().registered);
+        assert!(!world
+            .resource::<Input<InputAction>>()
+            .just_pressed(InputAction::Confirm));
+        let category = ScoreCategory::default();
+        assert_eq!(
+            world.resource::<HighScores>().entries(&category)[0].name,
+            "AC"
+        );
+    }
+}
@@ -1,13 +1,23 @@
 use bevy::prelude::*;
 
+pub mod chain;
+pub mod continue_prompt;
 pub mod destroy_enemy;
 pub mod enemy;
-mod game_frame;
+pub mod explosion;
+pub mod extend;
+pub mod game_frame;
+pub mod hitbox;
+pub mod item;
 pub mod life_count;
+pub mod pause;
 pub mod play_area;
 pub mod player;
+pub mod player_bomb;
+pub mod player_death;
 pub mod player_shot;
 pub mod player_stock;
+pub mod retry;
 pub mod scoreboard;
 mod system_label;
 
@@ -22,6 +32,16 @@ impl Plugin for InGamePlugin {
             .add_plugin(player_shot::PlayerShotPlugin)
             .add_plugin(enemy::EnemyPlugin)
             .add_plugin(destroy_enemy::DestroyEnemyPlugin)
-            .add_plugin(player_stock::PlayerStockPlugin);
+            .add_plugin(player_stock::PlayerStockPlugin)
+            .add_plugin(player_death::PlayerDeathPlugin)
+            .add_plugin(player_bomb::PlayerBombPlugin)
+            .add_plugin(explosion::ExplosionPlugin)
+            .add_plugin(extend::ExtendPlugin)
+            .add_plugin(item::ItemPlugin)
+            .add_plugin(chain::ChainPlugin)
+            .add_plugin(pause::PausePlugin)
+            .add_plugin(continue_prompt::ContinuePlugin)
+            .add_plugin(retry::RetryPlugin)
+            .add_plugin(hitbox::HitboxPlugin);
     }
 }
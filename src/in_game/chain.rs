@@ -0,0 +1,270 @@
+use super::game_frame::GameFrame;
+use crate::app_state::AppState;
+use crate::in_game::player::{Player, PlayerState};
+use crate::in_game::scoreboard::Score;
+use crate::WINDOW_WIDTH;
+use bevy::prelude::*;
+
+pub struct ChainPlugin;
+
+impl Plugin for ChainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChainConfig>()
+            .add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(update_chain_system)
+                    .with_system(display_chain_system.after(update_chain_system)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+pub struct ChainConfig {
+    // 最後に倒してからこのフレーム数のうちに次の敵を倒すとチェインが続く
+    pub window_frames: i32,
+    // 途切れた後、このフレーム数ごとにチェインが1ずつ減っていく
+    pub decay_interval_frames: i32,
+    // 1チェインごとに増えるスコアの倍率
+    pub multiplier_step: f64,
+    pub max_multiplier: f64,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self {
+            window_frames: 90,
+            decay_interval_frames: 4,
+            multiplier_step: 0.1,
+            max_multiplier: 4.0,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Chain {
+    count: u32,
+    // 0になるとチェインが減り始める
+    rest_frame: i32,
+}
+
+impl Chain {
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn multiplier(&self, config: &ChainConfig) -> f64 {
+        (1. + self.count as f64 * config.multiplier_step).min(config.max_multiplier)
+    }
+
+    // 敵を倒したときに呼ぶ。倍率は倒した敵のスコアにも掛かる
+    pub fn on_kill(&mut self, config: &ChainConfig, score: &mut Score) {
+        self.count += 1;
+        self.rest_frame = config.window_frames;
+        score.set_multiplier(self.multiplier(config));
+    }
+
+    fn decay(&mut self, config: &ChainConfig, score: &mut Score) {
+        if self.count == 0 {
+            return;
+        }
+        self.count -= 1;
+        self.rest_frame = config.decay_interval_frames;
+        score.set_multiplier(self.multiplier(config));
+    }
+
+    fn reset(&mut self, score: &mut Score) {
+        self.count = 0;
+        self.rest_frame = 0;
+        score.set_multiplier(1.);
+    }
+}
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct ChainEntity;
+
+#[derive(Component)]
+struct ChainText;
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Chain::default());
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+        font_size: 24.0,
+        color: Color::rgb(1.0, 1.0, 1.0),
+    };
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section("Chain", style.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(328.),
+                    left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ChainEntity);
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section("", style),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(352.),
+                    left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ChainText)
+        .insert(ChainEntity);
+}
+
+fn cleanup(mut commands: Commands, query: Query<Entity, With<ChainEntity>>) {
+    commands.remove_resource::<Chain>();
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn update_chain_system(
+    game_frame: Res<GameFrame>,
+    config: Res<ChainConfig>,
+    mut chain: ResMut<Chain>,
+    mut score: ResMut<Score>,
+    player_query: Query<&Player>,
+) {
+    if !game_frame.is_changed() {
+        return;
+    }
+
+    // 撃墜されるとチェインは途切れる
+    if matches!(player_query.single().state, PlayerState::Dead { .. }) {
+        if chain.count > 0 {
+            chain.reset(&mut score);
+        }
+        return;
+    }
+
+    if chain.rest_frame > 0 {
+        chain.rest_frame -= 1;
+    } else {
+        chain.decay(&config, &mut score);
+    }
+}
+
+fn display_chain_system(
+    game_frame: Res<GameFrame>,
+    config: Res<ChainConfig>,
+    chain: Res<Chain>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ChainText>>,
+) {
+    let (mut text, mut visibility) = query.single_mut();
+    text.sections[0].value = format!("x{:.1} ({})", chain.multiplier(&config), chain.count);
+    // 途切れて減っている間は点滅させる
+    let decaying = chain.count > 0 && chain.rest_frame <= config.decay_interval_frames;
+    visibility.is_visible = !decaying || (game_frame.0 / 8) % 2 == 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain_world(chain: Chain, score: Score) -> World {
+        let mut world = World::new();
+        world.insert_resource(GameFrame(0));
+        world.insert_resource(ChainConfig::default());
+        world.insert_resource(chain);
+        world.insert_resource(score);
+        world.spawn().insert(Player::default());
+        world
+    }
+
+    fn run_frames(world: &mut World, frames: i32) {
+        let mut stage = SystemStage::single_threaded().with_system(update_chain_system);
+        for _ in 0..frames {
+            world.resource_mut::<GameFrame>().0 += 1;
+            stage.run(world);
+        }
+    }
+
+    #[test]
+    fn multiplier_grows_per_kill_up_to_max() {
+        let config = ChainConfig::default();
+        let mut score = Score::new(3, 2);
+        let mut chain = Chain::default();
+
+        chain.on_kill(&config, &mut score);
+        assert!((chain.multiplier(&config) - 1.1).abs() < 1e-9);
+        score.add_score(100);
+        assert_eq!(score.score, 110);
+
+        for _ in 0..100 {
+            chain.on_kill(&config, &mut score);
+        }
+        assert_eq!(chain.multiplier(&config), config.max_multiplier);
+    }
+
+    #[test]
+    fn chain_decays_after_window() {
+        let config = ChainConfig::default();
+        let mut chain = Chain::default();
+        let mut score = Score::new(3, 2);
+        for _ in 0..3 {
+            chain.on_kill(&config, &mut score);
+        }
+        let mut world = chain_world(chain, score);
+
+        // 受付時間の間は減らない
+        run_frames(&mut world, config.window_frames);
+        assert_eq!(world.resource::<Chain>().count(), 3);
+
+        // 途切れると一定間隔で1ずつ減っていく
+        run_frames(&mut world, 1);
+        assert_eq!(world.resource::<Chain>().count(), 2);
+        run_frames(&mut world, config.decay_interval_frames);
+        assert_eq!(world.resource::<Chain>().count(), 2);
+        run_frames(&mut world, 1);
+        assert_eq!(world.resource::<Chain>().count(), 1);
+
+        // 減った分だけスコアの倍率も下がる
+        let mut score = world.resource_mut::<Score>();
+        score.add_score(100);
+        assert_eq!(score.score, 110);
+    }
+
+    #[test]
+    fn chain_resets_when_shot_down() {
+        let config = ChainConfig::default();
+        let mut chain = Chain::default();
+        let mut score = Score::new(3, 2);
+        for _ in 0..5 {
+            chain.on_kill(&config, &mut score);
+        }
+        let mut world = chain_world(chain, score);
+        let mut query = world.query::<&mut Player>();
+        query.single_mut(&mut world).state = PlayerState::Dead { rest_frame: 10 };
+
+        run_frames(&mut world, 1);
+        assert_eq!(world.resource::<Chain>().count(), 0);
+        let mut score = world.resource_mut::<Score>();
+        score.add_score(100);
+        assert_eq!(score.score, 100);
+    }
+}
@@ -0,0 +1,213 @@
+use super::scoreboard::Score;
+use crate::app_state::AppState;
+use crate::input_action::InputAction;
+use crate::settings::{key_name, Settings};
+use bevy::prelude::*;
+
+pub struct ContinuePlugin;
+
+impl Plugin for ContinuePlugin {
+    fn build(&self, app: &mut App) {
+        // 残機が尽きたらInGameの上にContinueを積むので、ゲームはその場で止まる
+        app.init_resource::<ContinueConfig>()
+            .add_system_set(SystemSet::on_enter(AppState::Continue).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Continue).with_system(continue_prompt_system),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Continue).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+// コンティニューの設定。アプリ側で上書きして調整できる
+pub struct ContinueConfig {
+    // 1回のプレイでコンティニューできる回数。0ならすぐにゲームオーバーになる
+    pub max_continues: u32,
+    // 何も押さずにいるとゲームオーバーになるまでの秒数
+    pub countdown_seconds: f32,
+}
+
+impl Default for ContinueConfig {
+    fn default() -> Self {
+        Self {
+            max_continues: 3,
+            countdown_seconds: 10.,
+        }
+    }
+}
+
+// 描画のフレームレートに左右されないように、経過時間でカウントダウンする
+struct ContinuePrompt(Timer);
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct ContinuePromptText;
+
+struct ContinuePromptData {
+    overlay_entity: Entity,
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, config: Res<ContinueConfig>) {
+    commands.insert_resource(ContinuePrompt(Timer::from_seconds(
+        config.countdown_seconds,
+        false,
+    )));
+
+    let overlay_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 32.0,
+                            color: Color::rgb(1., 1., 1.),
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    ..Default::default()
+                })
+                .insert(ContinuePromptText);
+        })
+        .id();
+    commands.insert_resource(ContinuePromptData { overlay_entity });
+}
+
+fn cleanup(mut commands: Commands, data: Res<ContinuePromptData>) {
+    commands.entity(data.overlay_entity).despawn_recursive();
+    commands.remove_resource::<ContinuePromptData>();
+    commands.remove_resource::<ContinuePrompt>();
+}
+
+// Confirmでコンティニュー、Cancelかカウントが0になるとゲームオーバー
+// 状態が切り替わった同じフレームで、戻った側が同じキー入力を拾わないように消しておく
+#[allow(clippy::too_many_arguments)]
+fn continue_prompt_system(
+    time: Res<Time>,
+    mut action_input: ResMut<Input<InputAction>>,
+    settings: Res<Settings>,
+    config: Res<ContinueConfig>,
+    mut prompt: ResMut<ContinuePrompt>,
+    mut score: ResMut<Score>,
+    mut state: ResMut<State<AppState>>,
+    mut query: Query<&mut Text, With<ContinuePromptText>>,
+) {
+    if action_input.clear_just_pressed(InputAction::Confirm) {
+        score.continue_game();
+        state.pop().unwrap();
+        return;
+    }
+    if action_input.clear_just_pressed(InputAction::Cancel)
+        || prompt.0.tick(time.delta()).finished()
+    {
+        // replaceはContinueを抜けてからInGameも抜けるので、ゲーム中の後片付けが走る
+        state.replace(AppState::GameOver).unwrap();
+        return;
+    }
+
+    let seconds = (prompt.0.duration() - prompt.0.elapsed())
+        .as_secs_f32()
+        .ceil() as i32;
+    let rest_continues = config.max_continues.saturating_sub(score.continue_count());
+    let key = |action: InputAction| key_name(action.key(&settings.key_bindings));
+    let mut text = query.single_mut();
+    text.sections[0].value = format!(
+        "CONTINUE?\n{}\n\nCredits: {}\n{}: Continue  {}: Give up",
+        seconds,
+        rest_continues,
+        key(InputAction::Confirm),
+        key(InputAction::Cancel)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt_world() -> (World, SystemStage) {
+        let mut world = World::new();
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(Time::default());
+        world.insert_resource(Input::<InputAction>::default());
+        world.insert_resource(Settings::default());
+        world.insert_resource(ContinueConfig::default());
+        world.insert_resource(ContinuePrompt(Timer::from_seconds(10., false)));
+        let mut score = Score::new(3, 2);
+        score.add_score(1000);
+        world.insert_resource(score);
+        world.spawn().insert_bundle((
+            Text::from_section("", TextStyle::default()),
+            ContinuePromptText,
+        ));
+        let mut driver =
+            SystemStage::single_threaded().with_system_set(State::<AppState>::get_driver());
+        driver.run(&mut world);
+        world
+            .resource_mut::<State<AppState>>()
+            .push(AppState::Continue)
+            .unwrap();
+        driver.run(&mut world);
+        (world, driver)
+    }
+
+    fn run_prompt(world: &mut World, driver: &mut SystemStage, action: Option<InputAction>) {
+        if let Some(action) = action {
+            world.resource_mut::<Input<InputAction>>().press(action);
+        }
+        SystemStage::single_threaded()
+            .with_system(continue_prompt_system)
+            .run(world);
+        driver.run(world);
+    }
+
+    #[test]
+    fn prompt_shows_bound_keys() {
+        let (mut world, mut driver) = prompt_world();
+        run_prompt(&mut world, &mut driver, None);
+        let mut query = world.query_filtered::<&Text, With<ContinuePromptText>>();
+        let text = &query.single(&world).sections[0].value;
+        assert!(text.ends_with("Return: Continue  Escape: Give up"));
+    }
+
+    #[test]
+    fn confirm_continues_from_where_it_stopped() {
+        let (mut world, mut driver) = prompt_world();
+        run_prompt(&mut world, &mut driver, Some(InputAction::Confirm));
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::InGame
+        );
+        let score = world.resource::<Score>();
+        assert_eq!(score.score, 0);
+        assert_eq!(score.continue_count(), 1);
+    }
+
+    #[test]
+    fn cancel_gives_up() {
+        let (mut world, mut driver) = prompt_world();
+        run_prompt(&mut world, &mut driver, Some(InputAction::Cancel));
+        let state = world.resource::<State<AppState>>();
+        assert_eq!(*state.current(), AppState::GameOver);
+        assert_eq!(state.inactives(), &[]);
+        assert_eq!(world.resource::<Score>().continue_count(), 0);
+    }
+}
@@ -1,46 +1,73 @@
-use super::enemy::Enemy;
+use super::chain::{Chain, ChainConfig};
+use super::enemy::{CancelBulletsEvent, CancelTarget, Enemy};
 use super::player_shot::Bullet as PlayerBullet;
+use super::system_label::GameSystemLabel;
 use crate::app_state::AppState;
+use crate::in_game::hitbox::{CollisionLayer, Hitbox, SpatialHash};
+use crate::in_game::item::spawn_drop_items;
 use crate::in_game::scoreboard::Score;
-use bevy::{prelude::*, sprite::collide_aabb::collide};
+use bevy::prelude::*;
 
 pub struct DestroyEnemyPlugin;
 
 impl Plugin for DestroyEnemyPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(
-            SystemSet::on_update(AppState::InGame).with_system(destroy_enemy_system),
+            SystemSet::on_update(AppState::InGame).with_system(
+                destroy_enemy_system
+                    .label(GameSystemLabel::StateTransition)
+                    .after(GameSystemLabel::CollisionBroadphase),
+            ),
         );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn destroy_enemy_system(
     mut state: ResMut<State<AppState>>,
     mut commands: Commands,
-    player_bullet_query: Query<(Entity, &PlayerBullet, &Transform)>,
-    mut enemy_query: Query<(Entity, &mut Enemy, &Transform)>,
+    spatial_hash: Res<SpatialHash>,
+    player_bullet_query: Query<(Entity, &PlayerBullet, &Transform, &Hitbox)>,
+    mut enemy_query: Query<(&mut Enemy, &Transform)>,
     mut score: ResMut<Score>,
+    chain_config: Res<ChainConfig>,
+    mut chain: ResMut<Chain>,
+    mut cancel_events: EventWriter<CancelBulletsEvent>,
 ) {
-    for (player_bullet_entity, _, player_bullet_transform) in player_bullet_query.iter() {
-        for (enemy_entity, mut enemy, enemy_transform) in enemy_query.iter_mut() {
-            let collision = collide(
+    for (player_bullet_entity, _, player_bullet_transform, player_bullet_hitbox) in
+        player_bullet_query.iter()
+    {
+        // 同じフレームで既に倒した敵には当たらない
+        let hit_enemy_entity = spatial_hash
+            .query(
+                CollisionLayer::Enemy,
                 player_bullet_transform.translation,
-                player_bullet_transform.scale.truncate(),
-                enemy_transform.translation,
-                enemy_transform.scale.truncate(),
-            );
+                player_bullet_hitbox,
+            )
+            .find(|entity| {
+                enemy_query
+                    .get(*entity)
+                    .map(|(enemy, _)| enemy.hp > 0)
+                    .unwrap_or(false)
+            });
 
-            if collision.is_some() {
-                enemy.hp -= 1;
-                commands.entity(player_bullet_entity).despawn();
-                if enemy.hp <= 0 {
-                    commands.entity(enemy_entity).despawn();
-                    score.add_score(enemy.bonus_score as u128);
-                    if enemy.is_boss_enemy {
-                        state.set(AppState::Ending).unwrap();
-                    }
+        if let Some(enemy_entity) = hit_enemy_entity {
+            let (mut enemy, enemy_transform) = enemy_query.get_mut(enemy_entity).unwrap();
+            enemy.hp -= 1;
+            commands.entity(player_bullet_entity).despawn();
+            if enemy.hp <= 0 {
+                commands.entity(enemy_entity).despawn();
+                chain.on_kill(&chain_config, &mut score);
+                score.add_score(enemy.bonus_score as u128);
+                spawn_drop_items(
+                    &mut commands,
+                    &enemy.drop_items,
+                    enemy_transform.translation,
+                );
+                if enemy.is_boss_enemy {
+                    cancel_events.send(CancelBulletsEvent(CancelTarget::Owner(enemy_entity)));
+                    state.set(AppState::Ending).unwrap();
                 }
-                break;
             }
         }
     }
@@ -6,10 +6,20 @@ mod normal;
 mod system_label;
 
 use crate::app_state::AppState;
+use crate::in_game::hitbox::Hitbox;
+use crate::in_game::item::ItemKind;
 pub use barrage::bullet::Bullet;
+pub use barrage::bullet_cancel::{CancelBulletsEvent, CancelTarget};
+pub use barrage::bullet_pool::ReleaseBullet;
+pub use barrage::simulator as barrage_simulator;
 use barrage::EnemyBarragePlugin;
+pub use barrage::{barrage_keys, BarrageRank, BarrageServer, BarrageStarter};
 use bevy::prelude::*;
+pub use boss::boss_phase_starts;
 use life_count::EnemyLifeCountPlugin;
+pub use system_label::EnemySystemLabel;
+
+pub const ENEMY_SIZE: f32 = 30.0;
 
 pub struct EnemyPlugin;
 
@@ -32,6 +42,10 @@ pub struct Enemy {
     pub hp: i32,
     pub bonus_score: i32,
     pub is_boss_enemy: bool,
+    // 撃破時に落とすアイテム
+    pub drop_items: Vec<ItemKind>,
+    // 自機と接触したときに被弾扱いにするか
+    pub body_collision: bool,
 }
 
 impl Default for Enemy {
@@ -41,10 +55,19 @@ impl Default for Enemy {
             hp: 1,
             bonus_score: 100,
             is_boss_enemy: false,
+            drop_items: vec![],
+            body_collision: true,
         }
     }
 }
 
+impl Enemy {
+    // 画像の余白を除いた本体部分の当たり判定
+    pub fn default_hitbox() -> Hitbox {
+        Hitbox::circle(ENEMY_SIZE / 2. - 2.)
+    }
+}
+
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Load & store assets
     let assets_holder = assets_holder::EnemyAssetsHolder {
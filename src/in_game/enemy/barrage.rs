@@ -1,8 +1,15 @@
+mod barrage_server;
 mod barrage_starter;
 pub mod bullet;
+pub mod bullet_cancel;
+pub mod bullet_pool;
+pub mod bullet_style;
 mod bulletml_runner;
 pub mod configuration;
+mod invocation;
 mod plugin;
+pub mod simulator;
 
-pub use barrage_starter::BarrageStarter;
-pub use plugin::EnemyBarragePlugin;
+pub use barrage_server::BarrageServer;
+pub use barrage_starter::{BarrageRank, BarrageStarter};
+pub use plugin::{barrage_keys, EnemyBarragePlugin};
@@ -0,0 +1,401 @@
+use super::bulletml_runner::BulletMLRunner;
+use bevy_bulletml::parse::BulletMLParser;
+use bevy_bulletml::{BulletML, Runner, State};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/*
+ * Resource
+ */
+// 読み込んだ弾幕。BulletMLServerの代わりに、弾幕を始めるのに必要な文書の情報も合わせて持つ
+#[derive(Default)]
+pub struct BarrageServer {
+    barrages: HashMap<String, Barrage>,
+}
+
+impl BarrageServer {
+    pub fn load(&mut self, key: &str, xml: &str) -> Result<(), anyhow::Error> {
+        let bml = Arc::new(BulletMLParser::new().parse(xml)?);
+        let doc = roxmltree::Document::parse(xml)?;
+        let root = doc.root_element();
+        let parameter_count = root
+            .children()
+            .filter(|node| {
+                node.is_element()
+                    && node.tag_name().name() == "action"
+                    && node
+                        .attribute("label")
+                        .map(|label| label.starts_with("top"))
+                        .unwrap_or(false)
+            })
+            .flat_map(|action| action.descendants())
+            .filter_map(|node| node.text())
+            .map(max_parameter_index)
+            .max()
+            .unwrap_or(0);
+        // bevy_bulletmlは文書の向きの型を公開しておらず、Runner::new以外では向きをStateに設定できない。
+        // 引数を渡すときはStateから始めるので、横向きの文書は引数を取れない
+        let horizontal = root.attribute("type") == Some("horizontal");
+        if horizontal && parameter_count > 0 {
+            return Err(anyhow::anyhow!(
+                "Horizontal barrage {} cannot take parameters",
+                key
+            ));
+        }
+        let simple_bullet_labels = Arc::new(SimpleBulletLabels::from_document(root));
+        self.barrages.insert(
+            key.to_string(),
+            Barrage {
+                bml,
+                parameter_count,
+                simple_bullet_labels,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Barrage> {
+        self.barrages.get(key)
+    }
+}
+
+pub struct Barrage {
+    bml: Arc<BulletML>,
+    // トップのアクションが参照している引数($1, $2...)の数
+    pub parameter_count: usize,
+    pub simple_bullet_labels: Arc<SimpleBulletLabels>,
+}
+
+impl Barrage {
+    // 弾幕を撃ち始めるランナー
+    pub fn top_runners(&self, parameters: &[f64]) -> Vec<Runner<BulletMLRunner>> {
+        // 引数が無ければ、文書の向きを引き継ぐRunner::newで全てのトップのアクションを実行する
+        if parameters.is_empty() {
+            return vec![Runner::new(BulletMLRunner, self.bml.clone())];
+        }
+
+        // Runner::newは引数を渡せないので、トップのアクションごとにStateから始める
+        // 縦向きと向きの指定なしはランナーの動きが同じなので、向きは指定しない
+        let bml = &self.bml;
+        bml.root
+            .children(&bml.arena)
+            .filter(|child| bml.arena[*child].get().is_top_action())
+            .map(|action| {
+                let state = State {
+                    bml: bml.clone(),
+                    bml_type: None,
+                    nodes: Box::new([action]),
+                    parameters: parameters.to_vec(),
+                };
+                Runner::new_from_state(BulletMLRunner, state)
+            })
+            .collect()
+    }
+}
+
+// アクションを持たない弾のラベル。create_simple_bulletには弾のノードが渡されないので、
+// 弾を撃つランナーごとに、撃つ弾のラベルが一つに決まる場合だけ読み込み時に求めておく
+#[derive(Default)]
+pub struct SimpleBulletLabels {
+    // トップのアクションが撃つ弾のラベル
+    pub top: Option<Arc<str>>,
+    // ラベル付きの<bullet>のアクションが撃つ弾のラベル
+    bullets: HashMap<String, Arc<str>>,
+}
+
+impl SimpleBulletLabels {
+    fn from_document(root: roxmltree::Node) -> Self {
+        let top_actions = root.children().filter(|node| {
+            is_element(node, "action")
+                && node
+                    .attribute("label")
+                    .map(|label| label.starts_with("top"))
+                    .unwrap_or(false)
+        });
+        let top = unique_label(root, top_actions);
+        let bullets = root
+            .descendants()
+            .filter(|node| is_element(node, "bullet") && has_action(node))
+            .filter_map(|bullet| {
+                let label = bullet.attribute("label")?;
+                let simple_label = unique_label(root, std::iter::once(bullet))?;
+                Some((label.to_string(), simple_label))
+            })
+            .collect();
+        Self { top, bullets }
+    }
+
+    // 指定したラベルの<bullet>から生成されたランナーが撃つ弾のラベル
+    pub fn get(&self, bullet_label: Option<&str>) -> Option<Arc<str>> {
+        bullet_label.and_then(|label| self.bullets.get(label).cloned())
+    }
+}
+
+/*
+ * Utils
+ */
+fn is_element(node: &roxmltree::Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn find_labelled<'a, 'input>(
+    root: roxmltree::Node<'a, 'input>,
+    name: &str,
+    label: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    root.descendants()
+        .find(|node| is_element(node, name) && node.attribute("label") == Some(label))
+}
+
+fn has_action(bullet: &roxmltree::Node) -> bool {
+    bullet
+        .children()
+        .any(|node| is_element(&node, "action") || is_element(&node, "actionRef"))
+}
+
+// ランナーが撃つアクションを持たない弾のラベルが全て同じならそのラベル
+fn unique_label<'a, 'input>(
+    root: roxmltree::Node<'a, 'input>,
+    scopes: impl Iterator<Item = roxmltree::Node<'a, 'input>>,
+) -> Option<Arc<str>> {
+    let mut labels = Vec::new();
+    let mut visited = HashSet::new();
+    for scope in scopes {
+        collect_simple_bullet_labels(root, scope, &mut labels, &mut visited);
+    }
+    let first = labels.first()?.clone()?;
+    if labels.iter().all(|label| label.as_deref() == Some(&*first)) {
+        Some(first.into())
+    } else {
+        None
+    }
+}
+
+// アクションを持つ弾は別のランナーになるので、その中までは辿らない
+fn collect_simple_bullet_labels<'a, 'input>(
+    root: roxmltree::Node<'a, 'input>,
+    node: roxmltree::Node<'a, 'input>,
+    labels: &mut Vec<Option<String>>,
+    visited: &mut HashSet<String>,
+) {
+    for child in node.children().filter(|n| n.is_element()) {
+        let fire = match child.tag_name().name() {
+            "fire" => Some(child),
+            "fireRef" => child
+                .attribute("label")
+                .and_then(|label| find_labelled(root, "fire", label)),
+            "action" | "repeat" => {
+                collect_simple_bullet_labels(root, child, labels, visited);
+                None
+            }
+            "actionRef" => {
+                let label = child.attribute("label").unwrap_or_default();
+                // 再帰的に参照するアクションを何度も辿らない
+                if visited.insert(label.to_string()) {
+                    if let Some(action) = find_labelled(root, "action", label) {
+                        collect_simple_bullet_labels(root, action, labels, visited);
+                    }
+                }
+                None
+            }
+            _ => None,
+        };
+        let bullet = fire.and_then(|fire| {
+            fire.children()
+                .find(|n| is_element(n, "bullet") || is_element(n, "bulletRef"))
+        });
+        if let Some(bullet) = bullet {
+            let label = bullet.attribute("label");
+            let resolved = if is_element(&bullet, "bulletRef") {
+                label.and_then(|label| find_labelled(root, "bullet", label))
+            } else {
+                Some(bullet)
+            };
+            if !resolved.map(|b| has_action(&b)).unwrap_or(false) {
+                labels.push(label.map(|l| l.to_string()));
+            }
+        }
+    }
+}
+// 式の中の$nのnの最大値。$rankや$randは数えない
+fn max_parameter_index(text: &str) -> usize {
+    text.split('$')
+        .skip(1)
+        .filter_map(|s| {
+            let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_game::enemy::barrage::bullet::Bullet;
+    use crate::in_game::enemy::barrage::bulletml_runner::BulletMLRunnerData;
+    use bevy::ecs::system::CommandQueue;
+    use bevy::prelude::*;
+
+    const NWAY: &str = r#"<?xml version="1.0"?>
+<bulletml type="vertical">
+    <action label="top">
+        <fire>
+            <direction type="absolute">$2 + $rank</direction>
+            <speed>$1</speed>
+            <bulletRef label="child"><param>$3</param></bulletRef>
+        </fire>
+    </action>
+    <bullet label="child">
+        <action>
+            <changeSpeed><speed>$1</speed><term>$4</term></changeSpeed>
+        </action>
+    </bullet>
+</bulletml>"#;
+
+    #[test]
+    fn counts_parameters_used_by_top_actions() {
+        let mut server = BarrageServer::default();
+        server.load("nway", NWAY).unwrap();
+
+        // 参照先の弾の$4は<param>で渡されるので数えない
+        let barrage = server.get("nway").unwrap();
+        assert_eq!(barrage.parameter_count, 3);
+        assert_eq!(barrage.top_runners(&[1., 2., 3.]).len(), 1);
+    }
+
+    // 右向き(90度)に撃つ弾の、実行後の向き
+    fn fired_direction(bulletml_type: &str) -> f64 {
+        let mut server = BarrageServer::default();
+        let xml = format!(
+            r#"<?xml version="1.0"?>
+<bulletml type="{}">
+    <action label="top">
+        <fire><direction type="absolute">90</direction><bullet /></fire>
+    </action>
+</bulletml>"#,
+            bulletml_type
+        );
+        server.load("right", &xml).unwrap();
+
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        for mut runner in server.get("right").unwrap().top_runners(&[]) {
+            runner.run(
+                &mut BulletMLRunnerData::default(),
+                &mut Bullet::default(),
+                &Vec3::ZERO,
+                &Vec3::ZERO,
+                &mut commands,
+            );
+        }
+        queue.apply(&mut world);
+        world.query::<&Bullet>().single(&world).direction
+    }
+
+    #[test]
+    fn keeps_document_type() {
+        assert_eq!(fired_direction("vertical"), 90.);
+        // 横向きの文書では右が0度になる
+        assert_eq!(fired_direction("horizontal"), 0.);
+    }
+
+    #[test]
+    fn horizontal_barrage_cannot_take_parameters() {
+        let mut server = BarrageServer::default();
+        let xml = NWAY.replace(r#"type="vertical""#, r#"type="horizontal""#);
+        assert!(server.load("nway", &xml).is_err());
+    }
+
+    #[test]
+    fn resolves_labels_of_bullets_without_action() {
+        let mut server = BarrageServer::default();
+        server
+            .load(
+                "labelled",
+                r#"<?xml version="1.0"?>
+<bulletml>
+    <action label="top">
+        <repeat>
+            <times>3</times>
+            <actionRef label="shot" />
+        </repeat>
+        <fire><bulletRef label="wave" /></fire>
+    </action>
+    <action label="shot">
+        <fire><bulletRef label="small" /></fire>
+    </action>
+    <bullet label="small" />
+    <bullet label="wave">
+        <action>
+            <fire><bullet label="needle" /></fire>
+            <fire><bullet /></fire>
+        </action>
+    </bullet>
+</bulletml>"#,
+            )
+            .unwrap();
+
+        // アクションを持つwaveは別のランナーになるので、トップのアクションが撃つのはsmallだけ
+        let labels = &server.get("labelled").unwrap().simple_bullet_labels;
+        assert_eq!(labels.top.as_deref(), Some("small"));
+        // ラベルの無い弾も撃つので、waveが撃つ弾のラベルは一つに決まらない
+        assert_eq!(labels.get(Some("wave")), None);
+        assert_eq!(labels.get(None), None);
+    }
+
+    #[test]
+    fn resolves_labels_for_bullets_fired_by_runner() {
+        let mut server = BarrageServer::default();
+        server
+            .load(
+                "wave",
+                r#"<?xml version="1.0"?>
+<bulletml>
+    <action label="top">
+        <fire><bullet /></fire>
+        <fire><bulletRef label="wave" /></fire>
+    </action>
+    <bullet label="wave">
+        <actionRef label="spread" />
+    </bullet>
+    <action label="spread">
+        <fire><bullet label="needle" /></fire>
+        <fireRef label="needle_fire" />
+    </action>
+    <fire label="needle_fire">
+        <bulletRef label="needle" />
+    </fire>
+</bulletml>"#,
+            )
+            .unwrap();
+
+        let labels = &server.get("wave").unwrap().simple_bullet_labels;
+        assert_eq!(labels.top, None);
+        assert_eq!(labels.get(Some("wave")).as_deref(), Some("needle"));
+    }
+
+    #[test]
+    fn shipped_barrages_use_style_labels() {
+        let mut server = BarrageServer::default();
+        for (key, label) in [
+            ("aim_triple", "big_red"),
+            ("triple", "needle_yellow"),
+            ("nway", "small_blue"),
+            ("circle", "curve_green"),
+        ] {
+            let xml = std::fs::read_to_string(format!("data/barrage/{}.xml", key)).unwrap();
+            server.load(key, &xml).unwrap();
+            let labels = &server.get(key).unwrap().simple_bullet_labels;
+            assert_eq!(labels.top.as_deref(), Some(label));
+        }
+    }
+
+    #[test]
+    fn max_parameter_index_ignores_rank_and_rand() {
+        assert_eq!(max_parameter_index("$rank * $2 + $rand * $10"), 10);
+        assert_eq!(max_parameter_index("180 * $rand"), 0);
+    }
+}
@@ -1,17 +1,30 @@
 use bevy::prelude::*;
-use bevy_bulletml::{BulletMLServer, Runner};
 
 use super::{
+    barrage_server::BarrageServer,
     bullet::{Bullet, BulletType},
-    bulletml_runner::{BulletMLRunner, BulletMLRunnerData},
+    bulletml_runner::BulletMLRunnerData,
+    invocation::BarrageInvocation,
 };
 
+// ゲーム中の弾幕に使う難易度
+pub struct BarrageRank(pub f64);
+
+impl Default for BarrageRank {
+    fn default() -> Self {
+        BarrageRank(0.5)
+    }
+}
+
 pub trait BarrageStarter {
+    // barrage_typeは弾幕名のほか、`circle(24, 2.5)`のように引数を付けて指定できる
     fn start_barrage(
         &mut self,
         transform: &Transform,
-        bulletml_server: &BulletMLServer,
+        barrage_server: &BarrageServer,
         barrage_type: &str,
+        rank: f64,
+        owner: Option<Entity>,
     ) -> Result<(), anyhow::Error>;
 }
 
@@ -19,14 +32,31 @@ impl BarrageStarter for Commands<'_, '_> {
     fn start_barrage(
         &mut self,
         transform: &Transform,
-        bulletml_server: &BulletMLServer,
+        barrage_server: &BarrageServer,
         barrage_type: &str,
+        rank: f64,
+        owner: Option<Entity>,
     ) -> Result<(), anyhow::Error> {
-        let bml = bulletml_server.get(barrage_type);
-        if let Some(bml) = bml {
+        let invocation = barrage_type.parse::<BarrageInvocation>()?;
+        let barrage = barrage_server
+            .get(&invocation.name)
+            .ok_or_else(|| anyhow::anyhow!("Failed to load barrage: {}", barrage_type))?;
+        // 足りない引数をBulletMLが参照するとランナーが落ちるので、数が合わなければ始めない
+        if invocation.parameters.len() != barrage.parameter_count {
+            return Err(anyhow::anyhow!(
+                "Barrage {} takes {} parameters but {} were given: {}",
+                invocation.name,
+                barrage.parameter_count,
+                invocation.parameters.len(),
+                barrage_type
+            ));
+        }
+
+        for runner in barrage.top_runners(&invocation.parameters) {
             self.spawn()
                 .insert(Bullet {
                     vanished: true,
+                    owner,
                     ..Default::default()
                 })
                 .insert(Transform {
@@ -34,12 +64,17 @@ impl BarrageStarter for Commands<'_, '_> {
                     ..Default::default()
                 })
                 .insert(BulletType::WithRunner {
-                    data: BulletMLRunnerData::default(),
-                    runner: Runner::new(BulletMLRunner, bml),
+                    data: BulletMLRunnerData {
+                        rank,
+                        owner,
+                        barrage: Some(invocation.name.as_str().into()),
+                        simple_bullet_labels: barrage.simple_bullet_labels.clone(),
+                        simple_bullet_label: barrage.simple_bullet_labels.top.clone(),
+                        ..Default::default()
+                    },
+                    runner,
                 });
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Failed to load barrage: {}", barrage_type))
         }
+        Ok(())
     }
 }
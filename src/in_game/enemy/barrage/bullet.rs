@@ -8,9 +8,22 @@ use bevy_bulletml::Runner;
  */
 #[derive(Component)]
 pub struct Bullet {
+    // 上向きを0度とした時計回りの角度
     pub direction: f64,
     pub speed: f64,
+    // 1フレームあたりの速度の変化量
+    pub acceleration: f64,
+    // 1フレームあたりの向きの変化量(度)。曲がる弾に使う
+    pub angular_velocity: f64,
+    pub max_speed: Option<f64>,
+    pub min_speed: Option<f64>,
     pub vanished: bool,
+    // 弾幕を撃ち始めた敵
+    pub owner: Option<Entity>,
+    // プールに戻されて、次に使われるのを待っている
+    pub released: bool,
+    // プールから取り出されるたびに増える
+    pub generation: u32,
 }
 
 impl Default for Bullet {
@@ -18,17 +31,59 @@ impl Default for Bullet {
         Self {
             direction: 0.,
             speed: 0.,
+            acceleration: 0.,
+            angular_velocity: 0.,
+            max_speed: None,
+            min_speed: None,
             vanished: false,
+            owner: None,
+            released: false,
+            generation: 0,
         }
     }
 }
 
 impl Bullet {
-    pub fn update(&self, transform: &mut Transform) {
-        transform.translation.x +=
-            (f64::sin(self.direction * std::f64::consts::PI / 180.) * self.speed) as f32;
-        transform.translation.y +=
-            (f64::cos(self.direction * std::f64::consts::PI / 180.) * self.speed) as f32;
+    pub fn new(direction: f64, speed: f64) -> Self {
+        Self {
+            direction,
+            speed,
+            ..Default::default()
+        }
+    }
+
+    // 1フレーム分、速度と向きを更新してから移動する
+    pub fn update(&mut self, transform: &mut Transform) {
+        self.speed += self.acceleration;
+        if let Some(max_speed) = self.max_speed {
+            self.speed = self.speed.min(max_speed);
+        }
+        if let Some(min_speed) = self.min_speed {
+            self.speed = self.speed.max(min_speed);
+        }
+        self.direction = (self.direction + self.angular_velocity).rem_euclid(360.);
+
+        let velocity = self.velocity();
+        transform.translation.x += velocity.x;
+        transform.translation.y += velocity.y;
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        let rad = self.direction.to_radians();
+        Vec2::new(
+            (f64::sin(rad) * self.speed) as f32,
+            (f64::cos(rad) * self.speed) as f32,
+        )
+    }
+
+    pub fn set_velocity(&mut self, velocity: Vec2) {
+        self.speed = velocity.length() as f64;
+        if self.speed > 0. {
+            self.direction = (velocity.x as f64)
+                .atan2(velocity.y as f64)
+                .to_degrees()
+                .rem_euclid(360.);
+        }
     }
 }
 
@@ -40,3 +95,50 @@ pub enum BulletType {
         runner: Runner<BulletMLRunner>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accelerates_and_turns_each_update() {
+        let mut bullet = Bullet {
+            acceleration: 0.5,
+            angular_velocity: 90.,
+            max_speed: Some(2.),
+            ..Bullet::new(0., 1.)
+        };
+        let mut transform = Transform::default();
+
+        bullet.update(&mut transform);
+        assert_eq!(bullet.speed, 1.5);
+        assert_eq!(bullet.direction, 90.);
+        assert!((transform.translation.x - 1.5).abs() < 1e-5);
+        assert!(transform.translation.y.abs() < 1e-5);
+
+        bullet.update(&mut transform);
+        assert_eq!(bullet.speed, 2.);
+        assert_eq!(bullet.direction, 180.);
+        assert!((transform.translation.y + 2.).abs() < 1e-5);
+
+        // 上限に達した後は加速しない
+        bullet.update(&mut transform);
+        assert_eq!(bullet.speed, 2.);
+        assert_eq!(bullet.direction, 270.);
+    }
+
+    #[test]
+    fn decelerates_down_to_min_speed() {
+        let mut bullet = Bullet {
+            acceleration: -0.4,
+            min_speed: Some(0.5),
+            ..Bullet::new(0., 1.)
+        };
+        let mut transform = Transform::default();
+
+        bullet.update(&mut transform);
+        assert!((bullet.speed - 0.6).abs() < 1e-9);
+        bullet.update(&mut transform);
+        assert_eq!(bullet.speed, 0.5);
+    }
+}
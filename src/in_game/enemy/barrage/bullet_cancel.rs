@@ -0,0 +1,161 @@
+use super::bullet::Bullet;
+use super::bullet_pool::ReleaseBullet;
+use crate::in_game::item::{spawn_item, ItemKind};
+use bevy::prelude::*;
+
+/*
+ * Event
+ */
+// 弾消し。消した弾は自機に飛んでいく得点アイテムになる
+pub struct CancelBulletsEvent(pub CancelTarget);
+
+pub enum CancelTarget {
+    // 画面上の全ての弾。弾幕の発射元はそのまま撃ち続ける
+    All,
+    // 中心から半径radius以内の弾
+    Within { center: Vec3, radius: f32 },
+    // 指定した敵が撃った弾。弾幕の発射元も止める
+    Owner(Entity),
+}
+
+impl CancelTarget {
+    fn matches(&self, bullet: &Bullet, position: Vec3) -> bool {
+        match self {
+            Self::All => true,
+            Self::Within { center, radius } => position.distance(*center) <= *radius,
+            Self::Owner(owner) => bullet.owner == Some(*owner),
+        }
+    }
+}
+
+/*
+ * System
+ */
+pub fn cancel_bullets_system(
+    mut commands: Commands,
+    mut cancel_events: EventReader<CancelBulletsEvent>,
+    query: Query<(Entity, &Bullet, &Transform)>,
+) {
+    let targets: Vec<&CancelTarget> = cancel_events.iter().map(|e| &e.0).collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    for (entity, bullet, transform) in query.iter() {
+        if bullet.released {
+            continue;
+        }
+        let position = transform.translation;
+        if bullet.vanished {
+            // 発射元(見えない弾)は、敵を指定したときだけ止める
+            let stop_emitter = targets.iter().any(|target| {
+                matches!(target, CancelTarget::Owner(_)) && target.matches(bullet, position)
+            });
+            if stop_emitter {
+                commands.add(ReleaseBullet::new(entity, bullet));
+            }
+            continue;
+        }
+
+        if targets
+            .iter()
+            .any(|target| target.matches(bullet, position))
+        {
+            commands.add(ReleaseBullet::new(entity, bullet));
+            spawn_item(&mut commands, ItemKind::CancelScore, position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_game::item::Item;
+    use bevy::ecs::event::Events;
+
+    fn spawn_bullet(world: &mut World, owner: Option<Entity>, vanished: bool, x: f32) -> Entity {
+        world
+            .spawn()
+            .insert_bundle((
+                Bullet {
+                    owner,
+                    vanished,
+                    ..Default::default()
+                },
+                Transform::from_xyz(x, 0., 0.),
+            ))
+            .id()
+    }
+
+    fn cancel(world: &mut World, target: CancelTarget) {
+        world.init_resource::<Events<CancelBulletsEvent>>();
+        world.send_event(CancelBulletsEvent(target));
+        SystemStage::single_threaded()
+            .with_system(cancel_bullets_system)
+            .run(world);
+    }
+
+    fn item_count(world: &mut World) -> usize {
+        world.query::<&Item>().iter(world).count()
+    }
+
+    #[test]
+    fn within_matches_bullets_inside_radius() {
+        let target = CancelTarget::Within {
+            center: Vec3::new(10., 0., 0.),
+            radius: 5.,
+        };
+        let bullet = Bullet::default();
+        assert!(target.matches(&bullet, Vec3::new(15., 0., 0.)));
+        assert!(!target.matches(&bullet, Vec3::new(15.1, 0., 0.)));
+    }
+
+    #[test]
+    fn owner_cancels_its_bullets_and_emitter_only() {
+        let mut world = World::new();
+        let owner = world.spawn().id();
+        let other = world.spawn().id();
+        let bullet = spawn_bullet(&mut world, Some(owner), false, 0.);
+        let emitter = spawn_bullet(&mut world, Some(owner), true, 0.);
+        let other_bullet = spawn_bullet(&mut world, Some(other), false, 0.);
+
+        cancel(&mut world, CancelTarget::Owner(owner));
+        assert!(world.get_entity(bullet).is_none());
+        assert!(world.get_entity(emitter).is_none());
+        assert!(world.get_entity(other_bullet).is_some());
+        // 発射元はアイテムにならない
+        assert_eq!(item_count(&mut world), 1);
+    }
+
+    #[test]
+    fn all_keeps_emitters_firing() {
+        let mut world = World::new();
+        let owner = world.spawn().id();
+        let bullet = spawn_bullet(&mut world, Some(owner), false, 0.);
+        let emitter = spawn_bullet(&mut world, Some(owner), true, 0.);
+
+        cancel(&mut world, CancelTarget::All);
+        assert!(world.get_entity(bullet).is_none());
+        assert!(world.get_entity(emitter).is_some());
+        assert_eq!(item_count(&mut world), 1);
+    }
+
+    #[test]
+    fn within_ignores_emitters_and_distant_bullets() {
+        let mut world = World::new();
+        let near = spawn_bullet(&mut world, None, false, 10.);
+        let far = spawn_bullet(&mut world, None, false, 100.);
+        let emitter = spawn_bullet(&mut world, None, true, 0.);
+
+        cancel(
+            &mut world,
+            CancelTarget::Within {
+                center: Vec3::ZERO,
+                radius: 50.,
+            },
+        );
+        assert!(world.get_entity(near).is_none());
+        assert!(world.get_entity(far).is_some());
+        assert!(world.get_entity(emitter).is_some());
+    }
+}
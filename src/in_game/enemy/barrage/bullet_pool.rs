@@ -0,0 +1,189 @@
+use super::bullet::{Bullet, BulletType};
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+
+pub const BULLET_POOL_SIZE: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1b_2a5e_8f3d_4e71_9a06_d2c4_b87e_1f01);
+pub const BULLET_POOL_ALLOCATED: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1b_2a5e_8f3d_4e71_9a06_d2c4_b87e_1f02);
+pub const BULLET_POOL_REUSE_RATE: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1b_2a5e_8f3d_4e71_9a06_d2c4_b87e_1f03);
+
+/*
+ * Resource
+ */
+// 画面外に出たり消されたりした弾のエンティティを、despawnせずに非表示にして使い回す
+#[derive(Default)]
+pub struct BulletPool {
+    free: Vec<Entity>,
+    // 新しく作ったエンティティの数
+    allocated: usize,
+    // プールから取り出して使い回した回数
+    reused: usize,
+}
+
+impl BulletPool {
+    // 待機中のエンティティの数
+    pub fn size(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    // 弾の生成のうち、使い回しでまかなえた割合
+    pub fn reuse_rate(&self) -> f64 {
+        let total = self.allocated + self.reused;
+        if total == 0 {
+            0.
+        } else {
+            self.reused as f64 / total as f64
+        }
+    }
+}
+
+/*
+ * Component
+ */
+// プールで管理する弾。弾幕の発射元(見た目を持たない)には付けない
+#[derive(Component)]
+pub struct PooledBullet;
+
+/*
+ * Command
+ */
+// 弾を片付ける。プールがあれば非表示にして戻し、無ければdespawnする
+// コンポーネントは付けたままにして、アーキタイプが変わらないようにする
+pub struct ReleaseBullet {
+    entity: Entity,
+    generation: u32,
+}
+
+impl ReleaseBullet {
+    pub fn new(entity: Entity, bullet: &Bullet) -> Self {
+        Self {
+            entity,
+            generation: bullet.generation,
+        }
+    }
+}
+
+impl Command for ReleaseBullet {
+    fn write(self, world: &mut World) {
+        let pooled = world.get::<PooledBullet>(self.entity).is_some()
+            && world.contains_resource::<BulletPool>();
+        if !pooled {
+            world.despawn(self.entity);
+            return;
+        }
+
+        let mut entity = world.entity_mut(self.entity);
+        let mut bullet = entity.get_mut::<Bullet>().unwrap();
+        // 同じフレームで既に片付けられた場合や、片付けた後に別の弾として使い回された場合は何もしない
+        if bullet.released || bullet.generation != self.generation {
+            return;
+        }
+        bullet.released = true;
+        // ランナーが持っているBulletMLへの参照を手放す
+        *entity.get_mut::<BulletType>().unwrap() = BulletType::Simple;
+        entity.get_mut::<Visibility>().unwrap().is_visible = false;
+        world.resource_mut::<BulletPool>().free.push(self.entity);
+    }
+}
+
+/*
+ * System
+ */
+pub fn setup_bullet_pool_diagnostic_system(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(BULLET_POOL_SIZE, "bullet_pool_size", 20));
+    diagnostics.add(Diagnostic::new(
+        BULLET_POOL_ALLOCATED,
+        "bullet_pool_allocated",
+        20,
+    ));
+    diagnostics.add(
+        Diagnostic::new(BULLET_POOL_REUSE_RATE, "bullet_pool_reuse_rate", 20).with_suffix("%"),
+    );
+}
+
+pub fn bullet_pool_diagnostic_system(
+    pool: Option<Res<BulletPool>>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    if let Some(pool) = pool {
+        diagnostics.add_measurement(BULLET_POOL_SIZE, || pool.size() as f64);
+        diagnostics.add_measurement(BULLET_POOL_ALLOCATED, || pool.allocated() as f64);
+        diagnostics.add_measurement(BULLET_POOL_REUSE_RATE, || pool.reuse_rate() * 100.);
+    }
+}
+
+/*
+ * Utils
+ */
+// 弾のエンティティを取り出す。プールが空なら新しく作る
+pub fn acquire_bullet_entity(world: &mut World) -> Entity {
+    if let Some(mut pool) = world.get_resource_mut::<BulletPool>() {
+        if let Some(entity) = pool.free.pop() {
+            pool.reused += 1;
+            return entity;
+        }
+        pool.allocated += 1;
+    }
+    world.spawn().insert(PooledBullet).id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::in_game::enemy::barrage::bullet_style::SpawnBullet;
+
+    fn spawn_bullet(world: &mut World) {
+        SpawnBullet::simple(None).write(world);
+    }
+
+    fn single_bullet(world: &mut World) -> (Entity, u32, bool) {
+        let mut query = world.query::<(Entity, &Bullet)>();
+        let (entity, bullet) = query.single(world);
+        (entity, bullet.generation, bullet.released)
+    }
+
+    #[test]
+    fn reuses_entity_without_changing_archetype() {
+        let mut world = World::new();
+        world.insert_resource(BulletPool::default());
+        spawn_bullet(&mut world);
+        let (entity, generation, _) = single_bullet(&mut world);
+        let archetype = world.entity(entity).archetype().id();
+
+        ReleaseBullet { entity, generation }.write(&mut world);
+        assert_eq!(world.entity(entity).archetype().id(), archetype);
+        assert_eq!(world.resource::<BulletPool>().size(), 1);
+
+        spawn_bullet(&mut world);
+        let (reused, _, released) = single_bullet(&mut world);
+        assert_eq!(reused, entity);
+        assert!(!released);
+        assert_eq!(world.entity(entity).archetype().id(), archetype);
+        assert_eq!(world.resource::<BulletPool>().reuse_rate(), 0.5);
+    }
+
+    #[test]
+    fn stale_release_does_not_release_reused_bullet() {
+        let mut world = World::new();
+        world.insert_resource(BulletPool::default());
+        spawn_bullet(&mut world);
+        let (entity, generation, _) = single_bullet(&mut world);
+
+        // 片付け、使い回し、古い弾への片付けが同じフレームに並んだ場合
+        ReleaseBullet { entity, generation }.write(&mut world);
+        spawn_bullet(&mut world);
+        ReleaseBullet { entity, generation }.write(&mut world);
+
+        let (_, new_generation, released) = single_bullet(&mut world);
+        assert_ne!(new_generation, generation);
+        assert!(!released);
+        assert_eq!(world.resource::<BulletPool>().size(), 0);
+    }
+}
@@ -0,0 +1,341 @@
+use super::bullet::{Bullet, BulletType};
+use super::bullet_pool::acquire_bullet_entity;
+use crate::in_game::hitbox::Hitbox;
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use bevy_bulletml::State;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path;
+use std::sync::Arc;
+
+// ラベルにも弾幕の名前にも対応するスタイルが無い弾に使うスタイル
+const DEFAULT_STYLE_LABEL: &str = "default";
+const DEFAULT_RUNNER_STYLE_LABEL: &str = "default_runner";
+
+/*
+ * Resource
+ */
+#[derive(Clone)]
+pub struct BulletStyle {
+    pub texture: Option<Handle<Image>>,
+    pub color: Color,
+    pub size: Vec2,
+    pub hitbox: Hitbox,
+    // 画像を進行方向に合わせて回転させる
+    pub rotate_to_direction: bool,
+    // 1フレームあたりの速度の変化量
+    pub acceleration: f64,
+    // 1フレームあたりの向きの変化量(度)
+    pub angular_velocity: f64,
+    pub max_speed: Option<f64>,
+    pub min_speed: Option<f64>,
+}
+
+impl BulletStyle {
+    fn builtin(color: Color) -> Self {
+        Self {
+            texture: None,
+            color,
+            size: Vec2::new(5.0, 5.0),
+            // 見た目より小さい丸い当たり判定にする
+            hitbox: Hitbox::circle(1.5),
+            rotate_to_direction: false,
+            acceleration: 0.,
+            angular_velocity: 0.,
+            max_speed: None,
+            min_speed: None,
+        }
+    }
+}
+
+// BulletMLの弾のラベル、または弾幕の名前と見た目の対応表
+pub struct BulletStyleTable {
+    styles: HashMap<String, BulletStyle>,
+}
+
+impl Default for BulletStyleTable {
+    fn default() -> Self {
+        let mut styles = HashMap::new();
+        styles.insert(
+            DEFAULT_STYLE_LABEL.to_string(),
+            BulletStyle::builtin(Color::rgb(1.0, 0.0, 1.0)),
+        );
+        styles.insert(
+            DEFAULT_RUNNER_STYLE_LABEL.to_string(),
+            BulletStyle::builtin(Color::rgb(1.0, 0.5, 0.7)),
+        );
+        Self { styles }
+    }
+}
+
+impl BulletStyleTable {
+    // 画面を出さないシミュレーターではasset_serverを渡さず、画像を読み込まない
+    pub fn load_file<P: AsRef<path::Path>>(
+        &mut self,
+        file_path: P,
+        asset_server: Option<&AssetServer>,
+    ) -> Result<(), anyhow::Error> {
+        let file = File::open(file_path)?;
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(file);
+        for result in rdr.records() {
+            let record = result?;
+            let label = record[0].trim().to_string();
+            // 画像を指定しない場合は単色の矩形になる
+            let texture = match (record[1].trim(), asset_server) {
+                ("", _) | (_, None) => None,
+                (image, Some(asset_server)) => Some(asset_server.load(image)),
+            };
+            let color = Color::rgb(
+                record[2].parse::<f32>()?,
+                record[3].parse::<f32>()?,
+                record[4].parse::<f32>()?,
+            );
+            let size = Vec2::new(record[5].parse::<f32>()?, record[6].parse::<f32>()?);
+            let hitbox = Hitbox::circle(record[7].parse::<f32>()?);
+            let rotate_to_direction = record[8].trim().parse::<i32>()? != 0;
+            let acceleration = record[9].trim().parse::<f64>()?;
+            let angular_velocity = record[10].trim().parse::<f64>()?;
+            // 空欄なら速度の上限・下限なし
+            let max_speed = parse_optional_f64(&record[11])?;
+            let min_speed = parse_optional_f64(&record[12])?;
+            self.styles.insert(
+                label,
+                BulletStyle {
+                    texture,
+                    color,
+                    size,
+                    hitbox,
+                    rotate_to_direction,
+                    acceleration,
+                    angular_velocity,
+                    max_speed,
+                    min_speed,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    // 弾のラベル、弾幕の名前、デフォルトの順に探す
+    pub fn get(
+        &self,
+        label: Option<&str>,
+        barrage: Option<&str>,
+        with_runner: bool,
+    ) -> &BulletStyle {
+        let default_label = if with_runner {
+            DEFAULT_RUNNER_STYLE_LABEL
+        } else {
+            DEFAULT_STYLE_LABEL
+        };
+        label
+            .and_then(|l| self.styles.get(l))
+            .or_else(|| barrage.and_then(|b| self.styles.get(b)))
+            .or_else(|| self.styles.get(default_label))
+            .unwrap_or_else(|| &self.styles[DEFAULT_STYLE_LABEL])
+    }
+}
+
+/*
+ * Component
+ */
+// 画像を進行方向に合わせて回転させるか
+#[derive(Component)]
+pub struct RotateToDirection(pub bool);
+
+/*
+ * Command
+ */
+// 弾の生成はスタイルの参照が必要なので、Worldに直接アクセスするコマンドで行う
+pub struct SpawnBullet {
+    pub position: Vec3,
+    pub direction: f64,
+    pub speed: f64,
+    pub owner: Option<Entity>,
+    pub label: Option<String>,
+    pub barrage: Option<Arc<str>>,
+    pub bullet_type: BulletType,
+}
+
+#[cfg(test)]
+impl SpawnBullet {
+    // テスト用に、原点から上向きに撃つ弾を作る
+    pub fn simple(label: Option<&str>) -> Self {
+        Self {
+            position: Vec3::ZERO,
+            direction: 0.,
+            speed: 1.,
+            owner: None,
+            label: label.map(|l| l.to_string()),
+            barrage: None,
+            bullet_type: BulletType::Simple,
+        }
+    }
+}
+
+impl Command for SpawnBullet {
+    fn write(self, world: &mut World) {
+        let with_runner = matches!(self.bullet_type, BulletType::WithRunner { .. });
+        let style = world
+            .get_resource::<BulletStyleTable>()
+            .map(|table| {
+                table
+                    .get(self.label.as_deref(), self.barrage.as_deref(), with_runner)
+                    .clone()
+            })
+            .unwrap_or_else(|| BulletStyle::builtin(Color::rgb(1.0, 0.0, 1.0)));
+
+        let entity = acquire_bullet_entity(world);
+        let mut entity = world.entity_mut(entity);
+        let transform = Transform {
+            translation: self.position,
+            rotation: direction_to_rotation(self.direction),
+            ..Default::default()
+        };
+        let sprite = Sprite {
+            color: style.color,
+            custom_size: Some(style.size),
+            ..Default::default()
+        };
+        let texture = style.texture.unwrap_or_default();
+        let bullet = Bullet {
+            acceleration: style.acceleration,
+            angular_velocity: style.angular_velocity,
+            max_speed: style.max_speed,
+            min_speed: style.min_speed,
+            owner: self.owner,
+            // 使い回すときは世代を進めて、前の弾に向けた片付けが効かないようにする
+            generation: entity
+                .get::<Bullet>()
+                .map(|bullet| bullet.generation.wrapping_add(1))
+                .unwrap_or(0),
+            ..Bullet::new(self.direction, self.speed)
+        };
+        let rotate = RotateToDirection(style.rotate_to_direction);
+
+        if entity.contains::<Bullet>() {
+            // プールから取り出したエンティティは、アーキタイプが変わらないように値だけ書き換える
+            *entity.get_mut::<Transform>().unwrap() = transform;
+            *entity.get_mut::<Sprite>().unwrap() = sprite;
+            *entity.get_mut::<Handle<Image>>().unwrap() = texture;
+            entity.get_mut::<Visibility>().unwrap().is_visible = true;
+            *entity.get_mut::<Bullet>().unwrap() = bullet;
+            *entity.get_mut::<BulletType>().unwrap() = self.bullet_type;
+            *entity.get_mut::<Hitbox>().unwrap() = style.hitbox;
+            *entity.get_mut::<RotateToDirection>().unwrap() = rotate;
+        } else {
+            entity
+                .insert_bundle(SpriteBundle {
+                    transform,
+                    sprite,
+                    texture,
+                    ..Default::default()
+                })
+                .insert_bundle((bullet, self.bullet_type, style.hitbox, rotate));
+        }
+    }
+}
+
+/*
+ * System
+ */
+pub fn rotate_bullet_to_direction_system(
+    mut query: Query<(&Bullet, &RotateToDirection, &mut Transform)>,
+) {
+    for (bullet, rotate, mut transform) in query.iter_mut() {
+        if !rotate.0 || bullet.released {
+            continue;
+        }
+        transform.rotation = direction_to_rotation(bullet.direction);
+    }
+}
+
+/*
+ * Utils
+ */
+fn parse_optional_f64(s: &str) -> Result<Option<f64>, anyhow::Error> {
+    match s.trim() {
+        "" => Ok(None),
+        s => Ok(Some(s.parse::<f64>()?)),
+    }
+}
+
+// 上向き0度の時計回りの角度を、z軸周りの回転に変換する
+fn direction_to_rotation(direction: f64) -> Quat {
+    Quat::from_rotation_z(-(direction as f32).to_radians())
+}
+
+// 弾のアクションの親(<bullet>)のラベルを、BulletMLの文書のラベル一覧から引く
+// アクションを持たない弾のラベルは、弾幕の読み込み時にSimpleBulletLabelsで求めておく
+pub fn bullet_label(state: &State) -> Option<String> {
+    let bml = &state.bml;
+    let bullet_node = bml.arena[*state.nodes.first()?].parent()?;
+    bml.bullet_refs
+        .iter()
+        .find(|(_, node)| **node == bullet_node)
+        .map(|(label, _)| label.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawned_bullet_takes_kinematics_from_style() {
+        let mut table = BulletStyleTable::default();
+        table.styles.insert(
+            "curve".to_string(),
+            BulletStyle {
+                acceleration: 0.1,
+                angular_velocity: 5.,
+                max_speed: Some(3.),
+                ..BulletStyle::builtin(Color::WHITE)
+            },
+        );
+        let mut world = World::new();
+        world.insert_resource(table);
+
+        SpawnBullet::simple(Some("curve")).write(&mut world);
+
+        let mut query = world.query::<(&mut Bullet, &mut Transform)>();
+        let (mut bullet, mut transform) = query.single_mut(&mut world);
+        assert_eq!(bullet.max_speed, Some(3.));
+        bullet.update(&mut transform);
+        assert!((bullet.speed - 1.1).abs() < 1e-9);
+        assert_eq!(bullet.direction, 5.);
+    }
+
+    #[test]
+    fn bullet_label_is_resolved_from_referenced_bullet() {
+        let bml = bevy_bulletml::parse::BulletMLParser::new()
+            .parse(
+                r#"<?xml version="1.0"?>
+<bulletml>
+    <action label="top">
+        <fire><bulletRef label="curve" /></fire>
+    </action>
+    <bullet label="curve">
+        <actionRef label="turn" />
+    </bullet>
+    <action label="turn">
+        <wait>1</wait>
+    </action>
+</bulletml>"#,
+            )
+            .unwrap();
+        let bullet_node = bml.bullet_refs["curve"];
+        let action_node = bullet_node.children(&bml.arena).next().unwrap();
+        let state = State {
+            bml: Arc::new(bml),
+            bml_type: None,
+            nodes: Box::new([action_node]),
+            parameters: vec![],
+        };
+
+        assert_eq!(bullet_label(&state), Some("curve".to_string()));
+    }
+}
@@ -1,17 +1,47 @@
+use super::barrage_server::SimpleBulletLabels;
 use super::bullet::{Bullet, BulletType};
+use super::bullet_style::{bullet_label, SpawnBullet};
 use bevy::prelude::*;
 use bevy_bulletml::{AppRunner, Runner, State};
 use rand::prelude::*;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+thread_local! {
+    // 弾幕の乱数。シミュレーターでは結果を再現できるようにシードを指定する
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+pub fn seed_barrage_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
 
 pub struct BulletMLRunner;
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct BulletMLRunnerData {
     pub turn: u32,
+    // 弾幕の難易度(0.0〜1.0)。子の弾にも引き継がれる
+    pub rank: f64,
+    // 弾幕を撃ち始めた敵。子の弾にも引き継がれる
+    pub owner: Option<Entity>,
+    // 弾幕の名前。ラベルの無い弾の見た目に使う
+    pub barrage: Option<Arc<str>>,
+    // 弾幕の文書から求めた、アクションを持たない弾のラベル。子の弾にも引き継がれる
+    pub simple_bullet_labels: Arc<SimpleBulletLabels>,
+    // このランナーが撃つ、アクションを持たない弾のラベル
+    pub simple_bullet_label: Option<Arc<str>>,
 }
 
 impl Default for BulletMLRunnerData {
     fn default() -> Self {
-        Self { turn: 0 }
+        Self {
+            turn: 0,
+            rank: 0.5,
+            owner: None,
+            barrage: None,
+            simple_bullet_labels: Arc::default(),
+            simple_bullet_label: None,
+        }
     }
 }
 
@@ -51,46 +81,67 @@ impl AppRunner<BulletMLRunnerData, Bullet> for BulletMLRunner {
         bullet.vanished = true;
     }
 
+    fn do_change_direction(
+        &mut self,
+        _: &mut BulletMLRunnerData,
+        direction: f64,
+        bullet: &mut Bullet,
+    ) {
+        bullet.direction = direction;
+    }
+
+    fn do_change_speed(&mut self, _: &mut BulletMLRunnerData, speed: f64, bullet: &mut Bullet) {
+        bullet.speed = speed;
+    }
+
+    // BulletMLの座標系はy軸が下向きなので、縦方向は符号を反転する
+    fn do_accel_x(&mut self, speed_x: f64, bullet: &mut Bullet) {
+        let velocity = bullet.velocity();
+        bullet.set_velocity(Vec2::new(speed_x as f32, velocity.y));
+    }
+
+    fn do_accel_y(&mut self, speed_y: f64, bullet: &mut Bullet) {
+        let velocity = bullet.velocity();
+        bullet.set_velocity(Vec2::new(velocity.x, -speed_y as f32));
+    }
+
+    fn get_bullet_speed_x(&self, bullet: &Bullet) -> f64 {
+        bullet.velocity().x as f64
+    }
+
+    fn get_bullet_speed_y(&self, bullet: &Bullet) -> f64 {
+        -bullet.velocity().y as f64
+    }
+
     fn get_default_speed(&self) -> f64 {
         1.
     }
 
-    fn get_rank(&self, _: &BulletMLRunnerData) -> f64 {
-        0.5
+    fn get_rank(&self, data: &BulletMLRunnerData) -> f64 {
+        data.rank
     }
 
     fn get_rand(&self, _: &mut BulletMLRunnerData) -> f64 {
-        let mut rng = rand::thread_rng();
-        rng.gen()
+        RNG.with(|rng| rng.borrow_mut().gen())
     }
 
     fn create_simple_bullet(
         &mut self,
-        _: &mut BulletMLRunnerData,
+        data: &mut BulletMLRunnerData,
         direction: f64,
         speed: f64,
         bullet_position: &Vec3,
         commands: &mut Commands,
     ) {
-        commands
-            .spawn_bundle(SpriteBundle {
-                transform: Transform {
-                    translation: *bullet_position,
-                    scale: Vec3::new(5., 5., 5.),
-                    ..Default::default()
-                },
-                sprite: Sprite {
-                    color: Color::rgb(1.0, 0.0, 1.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .insert(Bullet {
-                direction,
-                speed,
-                vanished: false,
-            })
-            .insert(BulletType::Simple);
+        commands.add(SpawnBullet {
+            position: *bullet_position,
+            direction,
+            speed,
+            owner: data.owner,
+            label: data.simple_bullet_label.as_deref().map(|l| l.to_string()),
+            barrage: data.barrage.clone(),
+            bullet_type: BulletType::Simple,
+        });
     }
 
     fn create_bullet(
@@ -102,29 +153,21 @@ impl AppRunner<BulletMLRunnerData, Bullet> for BulletMLRunner {
         bullet_position: &Vec3,
         commands: &mut Commands,
     ) {
+        let label = bullet_label(&state);
         let runner = Runner::new_from_state(BulletMLRunner, state);
+        let data = BulletMLRunnerData {
+            simple_bullet_label: data.simple_bullet_labels.get(label.as_deref()),
+            ..data.clone()
+        };
 
-        commands
-            .spawn_bundle(SpriteBundle {
-                transform: Transform {
-                    translation: *bullet_position,
-                    scale: Vec3::new(5., 5., 5.),
-                    ..Default::default()
-                },
-                sprite: Sprite {
-                    color: Color::rgb(1.0, 0.5, 0.7),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .insert(Bullet {
-                direction,
-                speed,
-                vanished: false,
-            })
-            .insert(BulletType::WithRunner {
-                data: *data,
-                runner,
-            });
+        commands.add(SpawnBullet {
+            position: *bullet_position,
+            direction,
+            speed,
+            owner: data.owner,
+            label,
+            barrage: data.barrage.clone(),
+            bullet_type: BulletType::WithRunner { data, runner },
+        });
     }
 }
@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+// `circle(24, 2.5)`のような弾幕の呼び出し。引数はBulletMLの$1, $2...として参照される
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarrageInvocation {
+    pub name: String,
+    pub parameters: Vec<f64>,
+}
+
+impl FromStr for BarrageInvocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, parameters) = match s.find('(') {
+            None => (s, vec![]),
+            Some(open) => {
+                let args = s[open + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| anyhow::anyhow!("Missing `)` in barrage invocation: {}", s))?;
+                let parameters = args
+                    .split(',')
+                    .map(|a| a.trim())
+                    .filter(|a| !a.is_empty())
+                    .map(|a| {
+                        a.parse::<f64>().map_err(|_| {
+                            anyhow::anyhow!("Invalid barrage parameter `{}` in: {}", a, s)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (s[..open].trim(), parameters)
+            }
+        };
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Empty barrage name: {}", s));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            parameters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<BarrageInvocation, anyhow::Error> {
+        s.parse::<BarrageInvocation>()
+    }
+
+    #[test]
+    fn parses_name_only() {
+        let invocation = parse(" circle ").unwrap();
+        assert_eq!(invocation.name, "circle");
+        assert!(invocation.parameters.is_empty());
+    }
+
+    #[test]
+    fn parses_parameters() {
+        let invocation = parse("nway(5, 15, -2.5)").unwrap();
+        assert_eq!(invocation.name, "nway");
+        assert_eq!(invocation.parameters, vec![5., 15., -2.5]);
+    }
+
+    #[test]
+    fn parses_empty_parentheses() {
+        let invocation = parse("circle()").unwrap();
+        assert_eq!(invocation.name, "circle");
+        assert!(invocation.parameters.is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_close_paren() {
+        assert!(parse("nway(5, 15").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_parameter() {
+        assert!(parse("nway(5, fast)").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_name() {
+        assert!(parse("").is_err());
+        assert!(parse("(1, 2)").is_err());
+    }
+}
@@ -1,6 +1,13 @@
 use super::bullet::Bullet;
-use super::BarrageStarter;
+use super::bullet_cancel::{cancel_bullets_system, CancelBulletsEvent};
+use super::bullet_pool::{
+    bullet_pool_diagnostic_system, setup_bullet_pool_diagnostic_system, BulletPool, PooledBullet,
+    ReleaseBullet,
+};
+use super::bullet_style::{rotate_bullet_to_direction_system, BulletStyleTable};
+use super::{BarrageRank, BarrageServer, BarrageStarter};
 use crate::app_state::AppState;
+use crate::difficulty::Difficulties;
 use crate::in_game::enemy::barrage::bullet::BulletType;
 
 use crate::in_game::enemy::barrage::configuration::BarrageConfiguration;
@@ -11,25 +18,48 @@ use crate::in_game::life_count::LifeCount;
 use crate::in_game::play_area::PlayAreaDescriptor;
 use crate::in_game::player::Player;
 use bevy::prelude::*;
-use bevy_bulletml::BulletMLServer;
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct EnemyBarragePlugin;
 
 impl Plugin for EnemyBarragePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup))
+        app.init_resource::<BarrageRank>()
+            .add_event::<CancelBulletsEvent>()
+            .add_startup_system(setup_bullet_pool_diagnostic_system)
+            .add_system(bullet_pool_diagnostic_system)
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
                     .with_system(start_barrage_system.before(EnemySystemLabel::LifeCount))
-                    .with_system(move_enemy_bullet_system)
-                    .with_system(despawn_bullet_system)
-                    .with_system(move_enemy_bullet_system)
-                    .with_system(update_bullet_system),
-            )
-            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+                    // ボスの形態変化で同じフレームに撃ち始めた弾幕は消さないように、
+                    // 弾幕を開始するシステムの後で弾消しを行う
+                    .with_system(
+                        cancel_bullets_system
+                            .after(EnemySystemLabel::LifeCount)
+                            .after(EnemySystemLabel::BulletMovement),
+                    ),
+            );
+        // 弾幕のプレビューでも弾の処理は同じものを使う
+        for state in [AppState::InGame, AppState::BarragePreview] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup))
+                .add_system_set(
+                    SystemSet::on_update(state.clone())
+                        .with_system(update_bullet_system.label(EnemySystemLabel::BulletRunner))
+                        .with_system(
+                            move_enemy_bullet_system
+                                .label(EnemySystemLabel::BulletMovement)
+                                .after(EnemySystemLabel::BulletRunner),
+                        )
+                        .with_system(
+                            rotate_bullet_to_direction_system
+                                .after(EnemySystemLabel::BulletMovement),
+                        )
+                        .with_system(despawn_bullet_system.after(EnemySystemLabel::BulletMovement)),
+                )
+                .add_system_set(SystemSet::on_exit(state).with_system(cleanup));
+        }
     }
 }
 
@@ -37,69 +67,96 @@ impl Plugin for EnemyBarragePlugin {
  * System
  */
 
-fn setup(mut commands: Commands) {
-    let bullet_ml_server = build_bulletml_server();
-    commands.insert_resource(bullet_ml_server);
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(build_barrage_server());
+
+    let mut bullet_style_table = BulletStyleTable::default();
+    bullet_style_table
+        .load_file("data/bullet_style.csv", Some(&asset_server))
+        .unwrap();
+    commands.insert_resource(bullet_style_table);
+    commands.insert_resource(BulletPool::default());
 }
 
-fn cleanup(mut commands: Commands, query: Query<Entity, With<Bullet>>) {
-    commands.remove_resource::<BulletMLServer>();
+fn cleanup(
+    mut commands: Commands,
+    mut cancel_events: ResMut<Events<CancelBulletsEvent>>,
+    query: Query<Entity, Or<(With<Bullet>, With<PooledBullet>)>>,
+) {
+    // やり直したときに前のプレイの弾消しが持ち越されないようにする
+    cancel_events.clear();
+    commands.remove_resource::<BarrageServer>();
+    commands.remove_resource::<BulletStyleTable>();
+    commands.remove_resource::<BulletPool>();
     for e in query.iter() {
         commands.entity(e).despawn_recursive();
     }
 }
 
-fn move_enemy_bullet_system(
+// 弾の移動はGameFrameごとにこのシステムで一度だけ行う
+pub(super) fn move_enemy_bullet_system(
     game_frame: Res<GameFrame>,
-    mut query: Query<(&Bullet, &mut Transform)>,
+    mut query: Query<(&mut Bullet, &mut Transform)>,
 ) {
     if game_frame.is_changed() && game_frame.0 > 0 {
-        for (bullet, mut transform) in query.iter_mut() {
+        for (mut bullet, mut transform) in query.iter_mut() {
+            if bullet.released {
+                continue;
+            }
             bullet.update(&mut transform);
         }
     }
 }
 
 fn start_barrage_system(
-    bulletml_server: Res<BulletMLServer>,
+    barrage_server: Res<BarrageServer>,
+    barrage_rank: Res<BarrageRank>,
+    difficulties: Res<Difficulties>,
     query: Query<
-        (&Transform, &LifeCount, &BarrageConfiguration),
+        (Entity, &Transform, &LifeCount, &BarrageConfiguration),
         (With<Enemy>, Changed<LifeCount>),
     >,
     mut commands: Commands,
 ) {
-    for (transform, life_count, barrage_conf) in query.iter() {
+    for (entity, transform, life_count, barrage_conf) in query.iter() {
         if let Some(barrage_type_name) =
             barrage_conf.get_barrage_type_for_life_count(life_count.count)
         {
             commands
-                .start_barrage(transform, &bulletml_server, &barrage_type_name)
+                .start_barrage(
+                    transform,
+                    &barrage_server,
+                    difficulties.current().barrage_type(&barrage_type_name),
+                    barrage_rank.0,
+                    Some(entity),
+                )
                 .unwrap();
         }
     }
 }
 
-fn update_bullet_system(
+pub(super) fn update_bullet_system(
     game_frame: Res<GameFrame>,
     mut commands: Commands,
-    mut bullet_query: Query<(&mut Bullet, &mut Transform, &mut BulletType), Without<Player>>,
+    mut bullet_query: Query<(&mut Bullet, &Transform, &mut BulletType), Without<Player>>,
     ship_query: Query<(&Player, &Transform), Without<Bullet>>,
 ) {
     if !game_frame.is_changed() {
         return;
     }
 
+    // 移動はmove_enemy_bullet_systemで行うので、ここではBulletMLの実行だけを行う
     let (_, player_transform) = ship_query.single();
-    for (mut bullet, mut transform, mut bullet_type) in &mut bullet_query.iter_mut() {
+    for (mut bullet, transform, mut bullet_type) in &mut bullet_query.iter_mut() {
+        if bullet.released {
+            continue;
+        }
         match *bullet_type {
-            BulletType::Simple => {
-                bullet.update(&mut transform);
-            }
+            BulletType::Simple => {}
             BulletType::WithRunner {
                 ref mut data,
                 ref mut runner,
             } => {
-                bullet.update(&mut transform);
                 runner.run(
                     data,
                     &mut bullet,
@@ -113,23 +170,26 @@ fn update_bullet_system(
     }
 }
 
-fn despawn_bullet_system(
+pub(super) fn despawn_bullet_system(
     play_area: Res<PlayAreaDescriptor>,
     mut commands: Commands,
     query: Query<(Entity, &Bullet, &Transform, &BulletType)>,
 ) {
     for (entity, bullet, transform, bullet_type) in query.iter() {
+        if bullet.released {
+            continue;
+        }
         match *bullet_type {
             BulletType::Simple => {
                 if play_area.is_outside(&transform.translation) {
-                    commands.entity(entity).despawn();
+                    commands.add(ReleaseBullet::new(entity, bullet));
                 }
             }
             BulletType::WithRunner { ref runner, .. } => {
                 if (play_area.is_outside(&transform.translation) || bullet.vanished)
                     && runner.is_end()
                 {
-                    commands.entity(entity).despawn();
+                    commands.add(ReleaseBullet::new(entity, bullet));
                 }
             }
         }
@@ -139,10 +199,30 @@ fn despawn_bullet_system(
 /*
  * Utils
  */
-fn build_bulletml_server() -> BulletMLServer {
-    let mut bulletml_server = BulletMLServer::new();
+pub(super) fn build_barrage_server() -> BarrageServer {
+    let mut barrage_server = BarrageServer::default();
+    for p in bulletml_file_paths() {
+        let key = bulletml_key(&p);
+        let xml = fs::read_to_string(p).unwrap();
+        barrage_server.load(&key, &xml).unwrap();
+    }
+
+    barrage_server
+}
+
+// BarrageServerに登録されている弾幕の名前の一覧
+pub fn barrage_keys() -> Vec<String> {
+    let mut keys: Vec<String> = bulletml_file_paths()
+        .iter()
+        .map(|p| bulletml_key(p))
+        .collect();
+    keys.sort();
+    keys
+}
+
+fn bulletml_file_paths() -> Vec<PathBuf> {
     let barrage_dir_entries = fs::read_dir("data/barrage").unwrap();
-    let bulletml_file_paths: Vec<PathBuf> = barrage_dir_entries
+    barrage_dir_entries
         .filter_map(|e| {
             let ent = e.unwrap();
             if !is_bulletml_file_entry(&ent) {
@@ -151,15 +231,12 @@ fn build_bulletml_server() -> BulletMLServer {
                 Some(ent.path())
             }
         })
-        .collect();
-
-    for p in bulletml_file_paths {
-        // Use filestem as a key name.
-        let key = p.file_stem().unwrap().to_str().unwrap().to_string();
-        bulletml_server.load_file(&key, p).unwrap();
-    }
+        .collect()
+}
 
-    bulletml_server
+fn bulletml_key(path: &Path) -> String {
+    // Use filestem as a key name.
+    path.file_stem().unwrap().to_str().unwrap().to_string()
 }
 
 fn is_bulletml_file_entry(entry: &fs::DirEntry) -> bool {
@@ -0,0 +1,141 @@
+use super::bullet::Bullet;
+use super::bullet_style::BulletStyleTable;
+use super::bulletml_runner::seed_barrage_rng;
+use super::plugin::{
+    build_barrage_server, despawn_bullet_system, move_enemy_bullet_system, update_bullet_system,
+};
+use super::BarrageStarter;
+use crate::in_game::enemy::system_label::EnemySystemLabel;
+use crate::in_game::game_frame::GameFrame;
+use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::in_game::player::Player;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use serde::Serialize;
+
+// あるフレームでの1発分の弾の状態
+#[derive(Debug, Clone, Serialize)]
+pub struct BulletSnapshot {
+    pub frame: i128,
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub direction: f64,
+    pub speed: f64,
+}
+
+// 画面を出さずに、ゲーム中と同じシステムで弾幕を動かす
+pub struct BarrageSimulator {
+    world: World,
+    stage: SystemStage,
+}
+
+impl BarrageSimulator {
+    // data/barrageにある弾幕をoriginから撃ち始める。自機はtargetに置いたまま動かさない
+    pub fn new(
+        barrage_type: &str,
+        origin: Vec3,
+        target: Vec3,
+        rank: f64,
+        seed: u64,
+    ) -> Result<Self, anyhow::Error> {
+        seed_barrage_rng(seed);
+
+        let mut world = World::new();
+        world.insert_resource(GameFrame::default());
+        world.insert_resource(PlayAreaDescriptor::default());
+        // 加速や曲がる弾の動きはスタイルで決まるので、ゲームと同じ表を使う
+        let mut bullet_style_table = BulletStyleTable::default();
+        bullet_style_table.load_file("data/bullet_style.csv", None)?;
+        world.insert_resource(bullet_style_table);
+        world
+            .spawn()
+            .insert(Player::default())
+            .insert(Transform::from_translation(target));
+
+        let barrage_server = build_barrage_server();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        commands.start_barrage(
+            &Transform::from_translation(origin),
+            &barrage_server,
+            barrage_type,
+            rank,
+            None,
+        )?;
+        queue.apply(&mut world);
+
+        let mut stage = SystemStage::single_threaded();
+        stage
+            .add_system(update_bullet_system.label(EnemySystemLabel::BulletRunner))
+            .add_system(
+                move_enemy_bullet_system
+                    .label(EnemySystemLabel::BulletMovement)
+                    .after(EnemySystemLabel::BulletRunner),
+            )
+            .add_system(despawn_bullet_system.after(EnemySystemLabel::BulletMovement));
+
+        Ok(Self { world, stage })
+    }
+
+    // 1フレーム進める
+    pub fn step(&mut self) {
+        self.world.resource_mut::<GameFrame>().0 += 1;
+        self.stage.run(&mut self.world);
+    }
+
+    pub fn frame(&self) -> i128 {
+        self.world.resource::<GameFrame>().0
+    }
+
+    // 画面上の弾の状態。弾幕の発射元(vanished)とプールで待機中の弾は含めない
+    pub fn snapshot(&mut self) -> Vec<BulletSnapshot> {
+        let frame = self.frame();
+        let mut snapshots: Vec<BulletSnapshot> = self
+            .world
+            .query::<(Entity, &Bullet, &Transform)>()
+            .iter(&self.world)
+            .filter(|(_, bullet, _)| !bullet.vanished && !bullet.released)
+            .map(|(entity, bullet, transform)| BulletSnapshot {
+                frame,
+                id: entity.id(),
+                x: transform.translation.x,
+                y: transform.translation.y,
+                direction: bullet.direction,
+                speed: bullet.speed,
+            })
+            .collect();
+        // 差分を取りやすいように並び順を固定する
+        snapshots.sort_by_key(|s| s.id);
+        snapshots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traces_acceleration_from_bullet_style() {
+        let mut simulator = BarrageSimulator::new(
+            "aim_accel",
+            Vec3::new(0., 200., 0.),
+            Vec3::new(0., -200., 0.),
+            0.5,
+            0,
+        )
+        .unwrap();
+
+        let mut speeds = Vec::new();
+        for _ in 0..80 {
+            simulator.step();
+            let snapshot = simulator.snapshot();
+            if let Some(first) = snapshot.first() {
+                speeds.push(first.speed);
+            }
+        }
+        // accel_needleは1フレームに0.03ずつ加速して、3.0で止まる
+        assert!((speeds[1] - speeds[0] - 0.03).abs() < 1e-9);
+        assert_eq!(*speeds.last().unwrap(), 3.0);
+    }
+}
@@ -1,5 +1,5 @@
 mod emerge;
 mod movement;
 
-pub use emerge::BossEnemyEmergePlugin;
+pub use emerge::{boss_phase_starts, BossEnemyEmergePlugin};
 pub use movement::BossEnemyMovementPlugin;
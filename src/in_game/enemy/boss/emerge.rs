@@ -1,12 +1,14 @@
 use super::movement::MovePattern;
 use crate::app_state::AppState;
+use crate::difficulty::{Difficulties, Difficulty};
 use crate::in_game::enemy::assets_holder::EnemyAssetsHolder;
 
-use crate::in_game::enemy::Enemy;
+use crate::in_game::enemy::{Enemy, ENEMY_SIZE};
 use crate::in_game::game_frame::GameFrame;
 use crate::in_game::life_count::LifeCount;
 use crate::in_game::play_area::PlayAreaDescriptor;
 use crate::in_game::system_label::GameSystemLabel;
+use crate::practice::Practice;
 use bevy::prelude::*;
 use std::collections::HashMap;
 
@@ -75,8 +77,10 @@ impl EnemyEmerge {
         commands: &mut Commands,
         play_area_descriptor: &PlayAreaDescriptor,
         assets_holder: &EnemyAssetsHolder,
+        difficulty: &Difficulty,
+        life_count: i128,
     ) {
-        let enemy_size = Vec3::new(30., 30., 30.);
+        let enemy_size = Vec3::new(ENEMY_SIZE, ENEMY_SIZE, ENEMY_SIZE);
         let emerge_list = self.emerge_map.get(&frame);
         if let Some(emerge_list) = emerge_list {
             for emerge in emerge_list.iter() {
@@ -95,12 +99,14 @@ impl EnemyEmerge {
                         ..Default::default()
                     })
                     .insert(Enemy {
-                        hp: emerge.hp,
+                        hp: difficulty.boss_hp(emerge.hp),
                         bonus_score: emerge.bonus_score,
                         is_boss_enemy: true,
+                        body_collision: false,
                         ..Default::default()
                     })
-                    .insert(LifeCount::default())
+                    .insert(Enemy::default_hitbox())
+                    .insert(LifeCount { count: life_count })
                     .insert(emerge.move_pattern.clone());
             }
         }
@@ -113,13 +119,36 @@ fn emerge_enemy_system(
     emerger: Res<EnemyEmerge>,
     play_area_descripter: Res<PlayAreaDescriptor>,
     assets_holder: Res<EnemyAssetsHolder>,
+    difficulties: Res<Difficulties>,
+    practice: Option<Res<Practice>>,
 ) {
     if game_frame.is_changed() {
+        // 練習モードでは途中の形態から始められる
+        let life_count = practice.and_then(|p| p.boss_life_count).unwrap_or(0);
         emerger.emerge(
             game_frame.0,
             &mut commands,
             &play_area_descripter,
             &assets_holder,
+            difficulties.current(),
+            life_count,
         );
     }
 }
+
+// 練習モードの開始地点にする、ボスの出現フレームと各形態の(弾幕名, LifeCount)
+pub fn boss_phase_starts() -> Vec<(i128, String, i128)> {
+    let enemy_emerge = EnemyEmerge::new();
+    let mut frames: Vec<i128> = enemy_emerge.emerge_map.keys().copied().collect();
+    frames.sort_unstable();
+
+    let mut result = vec![];
+    for frame in frames {
+        for emerge in enemy_emerge.emerge_map[&frame].iter() {
+            for (barrage_name, life_count) in emerge.move_pattern.action_calculater().phases() {
+                result.push((frame, barrage_name, life_count));
+            }
+        }
+    }
+    result
+}
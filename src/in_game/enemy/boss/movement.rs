@@ -4,14 +4,15 @@ mod move_pattern;
 use self::move_pattern::BossAction;
 pub use self::move_pattern::MovePattern;
 use crate::app_state::AppState;
-use crate::in_game::enemy::barrage::BarrageStarter;
+use crate::difficulty::Difficulties;
+use crate::in_game::enemy::barrage::{BarrageRank, BarrageServer, BarrageStarter};
 use crate::in_game::enemy::system_label::EnemySystemLabel;
 use crate::in_game::enemy::Enemy;
+use crate::in_game::enemy::{CancelBulletsEvent, CancelTarget};
 use crate::in_game::game_frame::GameFrame;
 use crate::in_game::life_count::LifeCount;
 use crate::in_game::play_area::PlayAreaDescriptor;
 use bevy::prelude::*;
-use bevy_bulletml::BulletMLServer;
 
 pub struct BossEnemyMovementPlugin;
 
@@ -24,18 +25,22 @@ impl Plugin for BossEnemyMovementPlugin {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn move_boss_system(
     game_frame: Res<GameFrame>,
     mut commands: Commands,
     play_area_descriptor: Res<PlayAreaDescriptor>,
-    bulletml_server: Res<BulletMLServer>,
-    mut query: Query<(&mut Transform, &MovePattern, &LifeCount), With<Enemy>>,
+    barrage_server: Res<BarrageServer>,
+    barrage_rank: Res<BarrageRank>,
+    difficulties: Res<Difficulties>,
+    mut cancel_events: EventWriter<CancelBulletsEvent>,
+    mut query: Query<(Entity, &mut Transform, &mut Enemy, &MovePattern, &LifeCount)>,
 ) {
     if game_frame.is_changed() {
-        for (mut transform, move_pattern, life_count) in query.iter_mut() {
-            let action = move_pattern
-                .action_calculater()
-                .action_for_life_count(life_count);
+        for (entity, mut transform, mut enemy, move_pattern, life_count) in query.iter_mut() {
+            let action_calculater = move_pattern.action_calculater();
+            enemy.body_collision = !action_calculater.is_entering(life_count);
+            let action = action_calculater.action_for_life_count(life_count);
             match action {
                 BossAction::MoveTo(new_position) => {
                     let play_area_translation = new_position + play_area_descriptor.origin;
@@ -43,8 +48,16 @@ fn move_boss_system(
                 }
                 BossAction::Stay => { /* do nothing */ }
                 BossAction::StartBarrrage(barrage_name) => {
+                    // 形態が変わるときは前の弾幕を止めて弾を消す
+                    cancel_events.send(CancelBulletsEvent(CancelTarget::Owner(entity)));
                     commands
-                        .start_barrage(&transform, &bulletml_server, &barrage_name)
+                        .start_barrage(
+                            &transform,
+                            &barrage_server,
+                            difficulties.current().barrage_type(&barrage_name),
+                            barrage_rank.0,
+                            Some(entity),
+                        )
                         .unwrap();
                 }
             }
@@ -15,6 +15,10 @@ pub enum BossAction {
 // ボスの新しい座標を計算する、更新が必要であればSome, 現在の座標のままでよければNoneを返す
 pub trait ActionCalculater {
     fn action_for_life_count(&self, life_count: &LifeCount) -> BossAction;
+    // 登場演出中かどうか。登場中は自機との接触判定を行わない
+    fn is_entering(&self, life_count: &LifeCount) -> bool;
+    // 形態ごとの(弾幕名, その形態を始めるLifeCount)。練習モードで途中の形態から始めるのに使う
+    fn phases(&self) -> Vec<(String, i128)>;
 }
 
 impl MovePattern {
@@ -117,6 +117,25 @@ impl ActionCalculater for Boss1ActionCalculater {
                 .unwrap()
         }
     }
+
+    fn is_entering(&self, life_count: &LifeCount) -> bool {
+        life_count.count <= Self::enter_area_frames()
+    }
+
+    fn phases(&self) -> Vec<(String, i128)> {
+        self.main_action_scenario
+            .barrage_start_frames()
+            .into_iter()
+            .map(|(barrage_name, frame)| {
+                // 登場直後の弾幕は登場シーンから始める
+                let life_count = match frame {
+                    Some(frame) => frame + Self::enter_area_frames() + 1,
+                    None => 0,
+                };
+                (barrage_name, life_count)
+            })
+            .collect()
+    }
 }
 
 /* 次に何をしてほしい行動を指示するコマンド */
@@ -199,6 +218,23 @@ impl Boss1ActionScenario {
         }
     }
 
+    // 弾幕ごとに、その直前の移動を始めるフレーム。直前に移動が無ければNone
+    // 弾幕を撃つ位置まで移動してから撃ち始められるように、移動から始める
+    fn barrage_start_frames(&self) -> Vec<(String, Option<i128>)> {
+        let mut result = vec![];
+        let mut last_move_start = None;
+        for (range, command) in self.commands.iter() {
+            match command {
+                Boss1ActionScenarioCommand::Move { .. } => last_move_start = Some(*range.start()),
+                Boss1ActionScenarioCommand::StartBarrrage { barrage_name } => {
+                    result.push((barrage_name.clone(), last_move_start.take()));
+                }
+                Boss1ActionScenarioCommand::Stay => {}
+            }
+        }
+        result
+    }
+
     fn run(&self, frame: i128) -> Option<BossAction> {
         let matched_command = self
             .commands
@@ -1,9 +1,11 @@
 use super::movement::MovePattern;
 use crate::app_state::AppState;
+use crate::difficulty::{Difficulties, Difficulty};
 use crate::in_game::enemy::assets_holder::EnemyAssetsHolder;
 use crate::in_game::enemy::barrage::configuration::BarrageConfiguration;
-use crate::in_game::enemy::Enemy;
+use crate::in_game::enemy::{Enemy, ENEMY_SIZE};
 use crate::in_game::game_frame::GameFrame;
+use crate::in_game::item::ItemKind;
 use crate::in_game::life_count::LifeCount;
 use crate::in_game::play_area::PlayAreaDescriptor;
 use crate::in_game::system_label::GameSystemLabel;
@@ -49,6 +51,8 @@ struct Emerge {
     move_pattern: MovePattern,
     barrage_pattern: String,
     barrage_start_life_count: i128,
+    drop_items: Vec<ItemKind>,
+    body_collision: bool,
 }
 
 impl Emerge {
@@ -78,6 +82,7 @@ impl EnemyEmerge {
         let file = File::open(file_path)?;
         let mut rdr = csv::ReaderBuilder::new()
             .has_headers(true)
+            .flexible(true)
             .from_reader(file);
         for result in rdr.records() {
             let record = result?;
@@ -87,6 +92,21 @@ impl EnemyEmerge {
             let move_pattern_index = record[3].parse::<i32>()?;
             let barrage_pattern = record[4].to_string();
             let barrage_start_life_count = record[5].parse::<i128>()?;
+            // 撃破時に落とすアイテム(省略可、複数の場合は`;`区切り、`small_score*3`で個数指定)
+            let drop_items = record
+                .get(6)
+                .unwrap_or("")
+                .split(';')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(parse_drop_item)
+                .collect::<Result<Vec<_>, _>>()?
+                .concat();
+            // 背景扱いの敵は0を指定して自機との接触判定を無効にする(省略時は有効)
+            let body_collision = match record.get(7).map(|s| s.trim()) {
+                None | Some("") => true,
+                Some(v) => v.parse::<i32>()? != 0,
+            };
             self.emerge_map
                 .entry(apper_frame)
                 .or_insert_with(Vec::new)
@@ -104,6 +124,8 @@ impl EnemyEmerge {
                     },
                     barrage_pattern,
                     barrage_start_life_count,
+                    drop_items,
+                    body_collision,
                 })
         }
 
@@ -116,8 +138,9 @@ impl EnemyEmerge {
         commands: &mut Commands,
         play_area_descriptor: &PlayAreaDescriptor,
         assets_holder: &EnemyAssetsHolder,
+        difficulty: &Difficulty,
     ) {
-        let enemy_size = Vec3::new(30., 30., 30.);
+        let enemy_size = Vec3::new(ENEMY_SIZE, ENEMY_SIZE, ENEMY_SIZE);
         let emerge_list = self.emerge_map.get(&frame);
         if let Some(emerge_list) = emerge_list {
             for emerge in emerge_list.iter() {
@@ -135,7 +158,13 @@ impl EnemyEmerge {
                         texture: assets_holder.blue.clone(),
                         ..Default::default()
                     })
-                    .insert(Enemy::default())
+                    .insert(Enemy {
+                        hp: difficulty.enemy_hp(Enemy::default().hp),
+                        drop_items: emerge.drop_items.clone(),
+                        body_collision: emerge.body_collision,
+                        ..Default::default()
+                    })
+                    .insert(Enemy::default_hitbox())
                     .insert(LifeCount::default())
                     .insert(emerge.build_barrage_configuration())
                     .insert(emerge.move_pattern.clone());
@@ -150,6 +179,7 @@ fn emerge_enemy_system(
     emerger: Res<EnemyEmerge>,
     play_area_descripter: Res<PlayAreaDescriptor>,
     assets_holder: Res<EnemyAssetsHolder>,
+    difficulties: Res<Difficulties>,
 ) {
     if game_frame.is_changed() {
         emerger.emerge(
@@ -157,6 +187,16 @@ fn emerge_enemy_system(
             &mut commands,
             &play_area_descripter,
             &assets_holder,
+            difficulties.current(),
         );
     }
 }
+
+// `power`や`small_score*3`のようなアイテムの指定
+fn parse_drop_item(s: &str) -> Result<Vec<ItemKind>, anyhow::Error> {
+    let (kind, count) = match s.split_once('*') {
+        Some((kind, count)) => (kind.trim(), count.trim().parse::<usize>()?),
+        None => (s, 1),
+    };
+    Ok(vec![kind.parse::<ItemKind>()?; count])
+}
@@ -2,4 +2,6 @@ use bevy::prelude::*;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 pub enum EnemySystemLabel {
     LifeCount,
+    BulletRunner,
+    BulletMovement,
 }
@@ -0,0 +1,90 @@
+use super::game_frame::GameFrame;
+use crate::app_state::AppState;
+use bevy::prelude::*;
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame).with_system(update_explosion_system),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+    }
+}
+
+/*
+ * Component
+ */
+#[derive(Component)]
+pub struct Explosion {
+    color: Color,
+    max_size: f32,
+    total_frame: i32,
+    rest_frame: i32,
+}
+
+/*
+ * System
+ */
+fn cleanup(mut commands: Commands, query: Query<Entity, With<Explosion>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn update_explosion_system(
+    game_frame: Res<GameFrame>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Explosion, &mut Transform, &mut Sprite)>,
+) {
+    if !game_frame.is_changed() {
+        return;
+    }
+
+    for (entity, mut explosion, mut transform, mut sprite) in query.iter_mut() {
+        explosion.rest_frame -= 1;
+        if explosion.rest_frame <= 0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // 時間経過で広がりながら薄くなる
+        let progress = 1.0 - explosion.rest_frame as f32 / explosion.total_frame as f32;
+        let size = explosion.max_size * progress;
+        transform.scale = Vec3::new(size, size, 1.0);
+        sprite.color = explosion.color;
+        sprite.color.set_a(1.0 - progress);
+    }
+}
+
+/*
+ * Utils
+ */
+pub fn spawn_explosion(
+    commands: &mut Commands,
+    position: Vec3,
+    color: Color,
+    max_size: f32,
+    frames: i32,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(position.x, position.y, 0.5),
+                scale: Vec3::ZERO,
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Explosion {
+            color,
+            max_size,
+            total_frame: frames,
+            rest_frame: frames,
+        });
+}
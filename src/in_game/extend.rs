@@ -0,0 +1,243 @@
+use super::game_frame::GameFrame;
+use crate::app_state::AppState;
+use crate::in_game::scoreboard::Score;
+use crate::{FPS, WINDOW_WIDTH};
+use bevy::prelude::*;
+
+const EXTEND_NOTIFICATION_FRAMES: i32 = (FPS * 2.) as i32;
+
+pub struct ExtendPlugin;
+
+impl Plugin for ExtendPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExtendConfig>()
+            .add_event::<ExtendEvent>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(extend_by_score_system)
+                    .with_system(apply_extend_system.after(extend_by_score_system))
+                    .with_system(update_extend_notification_system),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+pub enum ExtendThresholds {
+    // 一定スコアごとにエクステンド
+    Every(i128),
+    // 指定したスコアに到達したときにエクステンド
+    Fixed(Vec<i128>),
+}
+
+impl ExtendThresholds {
+    // scoreまでに到達したエクステンドの回数
+    pub fn reached_count(&self, score: i128) -> usize {
+        match self {
+            Self::Every(interval) => {
+                if *interval <= 0 {
+                    0
+                } else {
+                    (score / interval).max(0) as usize
+                }
+            }
+            Self::Fixed(scores) => scores.iter().filter(|s| **s <= score).count(),
+        }
+    }
+}
+
+pub struct ExtendConfig {
+    pub thresholds: ExtendThresholds,
+    // 残機の上限
+    pub max_stock: i32,
+}
+
+impl Default for ExtendConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: ExtendThresholds::Every(50_000),
+            max_stock: 8,
+        }
+    }
+}
+
+/*
+ * Event
+ */
+// スコアやアイテムによるエクステンド
+pub struct ExtendEvent;
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct ExtendNotification {
+    rest_frame: i32,
+}
+
+/*
+ * System
+ */
+fn cleanup(
+    mut commands: Commands,
+    mut extend_events: ResMut<Events<ExtendEvent>>,
+    query: Query<Entity, With<ExtendNotification>>,
+) {
+    // やり直したときに前のプレイのエクステンドが持ち越されないようにする
+    extend_events.clear();
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn extend_by_score_system(
+    config: Res<ExtendConfig>,
+    mut score: ResMut<Score>,
+    mut extend_events: EventWriter<ExtendEvent>,
+) {
+    if !score.is_changed() {
+        return;
+    }
+
+    let reached_count = config.thresholds.reached_count(score.score);
+    while score.score_extend_count() < reached_count {
+        score.count_score_extend();
+        extend_events.send(ExtendEvent);
+    }
+}
+
+fn apply_extend_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<ExtendConfig>,
+    mut score: ResMut<Score>,
+    mut extend_events: EventReader<ExtendEvent>,
+    notification_query: Query<Entity, With<ExtendNotification>>,
+) {
+    let mut extended = false;
+    for _ in extend_events.iter() {
+        score.extend(config.max_stock);
+        extended = true;
+    }
+
+    if !extended {
+        return;
+    }
+
+    // 表示中の通知は新しいもので置き換える
+    for e in notification_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    spawn_extend_notification(&mut commands, &asset_server);
+}
+
+fn update_extend_notification_system(
+    game_frame: Res<GameFrame>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ExtendNotification, &mut Visibility)>,
+) {
+    if !game_frame.is_changed() {
+        return;
+    }
+
+    for (entity, mut notification, mut visibility) in query.iter_mut() {
+        notification.rest_frame -= 1;
+        if notification.rest_frame <= 0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // 点滅させる
+        visibility.is_visible = (notification.rest_frame / 8) % 2 == 0;
+    }
+}
+
+/*
+ * Utils
+ */
+fn spawn_extend_notification(commands: &mut Commands, asset_server: &AssetServer) {
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section(
+                "EXTEND!",
+                TextStyle {
+                    font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(0.3, 1.0, 0.3),
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(250.),
+                    left: Val::Px(WINDOW_WIDTH / 3. - 80.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(ExtendNotification {
+            rest_frame: EXTEND_NOTIFICATION_FRAMES,
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 残機が無くなるまでの被弾回数
+    fn stock_of(score: &mut Score) -> i32 {
+        let mut stock = 0;
+        while score.has_stock() {
+            score.on_hit_enemy_bullet();
+            stock += 1;
+        }
+        stock
+    }
+
+    #[test]
+    fn every_counts_each_interval() {
+        let thresholds = ExtendThresholds::Every(50_000);
+        assert_eq!(thresholds.reached_count(0), 0);
+        assert_eq!(thresholds.reached_count(49_999), 0);
+        assert_eq!(thresholds.reached_count(50_000), 1);
+        assert_eq!(thresholds.reached_count(120_000), 2);
+        assert_eq!(ExtendThresholds::Every(0).reached_count(120_000), 0);
+    }
+
+    #[test]
+    fn fixed_counts_reached_scores() {
+        let thresholds = ExtendThresholds::Fixed(vec![10_000, 30_000, 100_000]);
+        assert_eq!(thresholds.reached_count(9_999), 0);
+        assert_eq!(thresholds.reached_count(30_000), 2);
+        assert_eq!(thresholds.reached_count(1_000_000), 3);
+    }
+
+    #[test]
+    fn extends_once_per_threshold_up_to_max_stock() {
+        let mut world = World::new();
+        world.insert_resource(ExtendConfig {
+            thresholds: ExtendThresholds::Every(1_000),
+            max_stock: 4,
+        });
+        world.insert_resource(Score::new(2, 0));
+        world.init_resource::<Events<ExtendEvent>>();
+        let mut stage = SystemStage::single_threaded().with_system(extend_by_score_system);
+
+        // 一度に3つの閾値を超えたら3回エクステンドし、同じ閾値では繰り返さない
+        world.resource_mut::<Score>().add_score(3_500);
+        stage.run(&mut world);
+        stage.run(&mut world);
+        let events = world.resource::<Events<ExtendEvent>>();
+        let mut reader = events.get_reader();
+        assert_eq!(reader.iter(events).count(), 3);
+
+        let mut score = world.resource_mut::<Score>();
+        for _ in 0..3 {
+            score.extend(4);
+        }
+        assert_eq!(stock_of(&mut score), 4);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::practice::Practice;
 use crate::{app_state::AppState, FPS};
 use bevy::prelude::*;
 
@@ -7,13 +8,15 @@ pub struct GameFramePlugin;
 
 impl Plugin for GameFramePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup))
-            .add_system_set(
-                SystemSet::on_update(AppState::InGame)
-                    .with_system(update_game_frame_system)
-                    .label(GameSystemLabel::GameFrameUpdate),
-            )
-            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+        for state in [AppState::InGame, AppState::BarragePreview] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup))
+                .add_system_set(
+                    SystemSet::on_update(state.clone())
+                        .with_system(update_game_frame_system)
+                        .label(GameSystemLabel::GameFrameUpdate),
+                )
+                .add_system_set(SystemSet::on_exit(state).with_system(cleanup));
+        }
     }
 }
 
@@ -33,8 +36,10 @@ impl Default for GameFrameTimer {
     }
 }
 
-fn setup(mut commands: Commands) {
-    commands.insert_resource(GameFrame::default());
+fn setup(mut commands: Commands, practice: Option<Res<Practice>>) {
+    // 練習モードでは、開始フレームに出現する敵から出てくるように1つ手前から始める
+    let frame = practice.map(|p| (p.start_frame - 1).max(0)).unwrap_or(0);
+    commands.insert_resource(GameFrame(frame));
     commands.insert_resource(GameFrameTimer::default());
 }
 
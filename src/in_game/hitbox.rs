@@ -0,0 +1,160 @@
+mod spatial_hash;
+
+use super::enemy::{Bullet as EnemyBullet, Enemy, EnemySystemLabel};
+use super::item::Item;
+use super::player_shot::Bullet as PlayerBullet;
+use super::system_label::GameSystemLabel;
+use crate::app_state::AppState;
+use crate::in_game::play_area::PlayAreaDescriptor;
+use bevy::prelude::*;
+pub use spatial_hash::{CollisionLayer, SpatialHash};
+
+const SPATIAL_HASH_CELL_SIZE: f32 = 32.0;
+
+pub struct HitboxPlugin;
+
+impl Plugin for HitboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame).with_system(
+                    // 弾と自機が動いた後の位置で判定する
+                    rebuild_spatial_hash_system
+                        .label(GameSystemLabel::CollisionBroadphase)
+                        .after(EnemySystemLabel::BulletMovement)
+                        .after(GameSystemLabel::PlayerMovement),
+                ),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+    }
+}
+
+/*
+ * Component
+ */
+// 当たり判定の形状。見た目の大きさ(Transformのscale)とは独立して設定する
+#[derive(Debug, Clone, Copy)]
+pub enum HitboxShape {
+    Circle { radius: f32 },
+    // 回転は考慮しない軸平行の矩形
+    Rect { size: Vec2 },
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Hitbox {
+    pub shape: HitboxShape,
+    // エンティティの位置からのずれ
+    pub offset: Vec2,
+}
+
+impl Hitbox {
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            shape: HitboxShape::Circle { radius },
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn rect(size: Vec2) -> Self {
+        Self {
+            shape: HitboxShape::Rect { size },
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    // 判定の中心のワールド座標
+    pub fn center(&self, position: Vec3) -> Vec2 {
+        position.truncate() + self.offset
+    }
+
+    // 判定を囲む矩形の半分の大きさ
+    pub fn half_extents(&self) -> Vec2 {
+        match self.shape {
+            HitboxShape::Circle { radius } => Vec2::splat(radius),
+            HitboxShape::Rect { size } => size / 2.,
+        }
+    }
+
+    pub fn intersects(&self, position: Vec3, other: &Hitbox, other_position: Vec3) -> bool {
+        let a = self.center(position);
+        let b = other.center(other_position);
+        match (self.shape, other.shape) {
+            (HitboxShape::Circle { radius: ra }, HitboxShape::Circle { radius: rb }) => {
+                a.distance_squared(b) <= (ra + rb) * (ra + rb)
+            }
+            (HitboxShape::Rect { size: sa }, HitboxShape::Rect { size: sb }) => {
+                let d = (a - b).abs();
+                let limit = (sa + sb) / 2.;
+                d.x <= limit.x && d.y <= limit.y
+            }
+            (HitboxShape::Circle { radius }, HitboxShape::Rect { size }) => {
+                circle_intersects_rect(a, radius, b, size)
+            }
+            (HitboxShape::Rect { size }, HitboxShape::Circle { radius }) => {
+                circle_intersects_rect(b, radius, a, size)
+            }
+        }
+    }
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, play_area: Res<PlayAreaDescriptor>) {
+    commands.insert_resource(SpatialHash::new(&play_area, SPATIAL_HASH_CELL_SIZE));
+}
+
+fn cleanup(mut commands: Commands) {
+    commands.remove_resource::<SpatialHash>();
+}
+
+fn rebuild_spatial_hash_system(
+    mut spatial_hash: ResMut<SpatialHash>,
+    player_shot_query: Query<(Entity, &Transform, &Hitbox), With<PlayerBullet>>,
+    enemy_query: Query<(Entity, &Transform, &Hitbox), With<Enemy>>,
+    enemy_bullet_query: Query<(Entity, &Transform, &Hitbox, &EnemyBullet)>,
+    item_query: Query<(Entity, &Transform, &Hitbox), With<Item>>,
+) {
+    spatial_hash.clear();
+    insert_all(
+        &mut spatial_hash,
+        CollisionLayer::PlayerShot,
+        player_shot_query.iter(),
+    );
+    insert_all(&mut spatial_hash, CollisionLayer::Enemy, enemy_query.iter());
+    // プールで待機中の弾は判定しない
+    insert_all(
+        &mut spatial_hash,
+        CollisionLayer::EnemyBullet,
+        enemy_bullet_query
+            .iter()
+            .filter(|(_, _, _, bullet)| !bullet.released)
+            .map(|(entity, transform, hitbox, _)| (entity, transform, hitbox)),
+    );
+    insert_all(&mut spatial_hash, CollisionLayer::Item, item_query.iter());
+}
+
+/*
+ * Utils
+ */
+fn insert_all<'a>(
+    spatial_hash: &mut SpatialHash,
+    layer: CollisionLayer,
+    entries: impl Iterator<Item = (Entity, &'a Transform, &'a Hitbox)>,
+) {
+    for (entity, transform, hitbox) in entries {
+        spatial_hash.insert(layer, entity, transform.translation, *hitbox);
+    }
+}
+
+fn circle_intersects_rect(circle_center: Vec2, radius: f32, rect_center: Vec2, size: Vec2) -> bool {
+    let half = size / 2.;
+    // 矩形内で円の中心に最も近い点との距離で判定する
+    let closest = circle_center.clamp(rect_center - half, rect_center + half);
+    circle_center.distance_squared(closest) <= radius * radius
+}
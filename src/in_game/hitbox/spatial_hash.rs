@@ -0,0 +1,118 @@
+use super::Hitbox;
+use crate::in_game::play_area::PlayAreaDescriptor;
+use bevy::prelude::*;
+
+// 当たり判定の対象の種類ごとにグリッドを分ける
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionLayer {
+    PlayerShot,
+    Enemy,
+    EnemyBullet,
+    Item,
+}
+
+const LAYER_COUNT: usize = 4;
+
+struct Entry {
+    entity: Entity,
+    position: Vec3,
+    hitbox: Hitbox,
+}
+
+struct Layer {
+    cells: Vec<Vec<Entry>>,
+    // 登録されている判定のうち最大のもの。検索範囲をこの分だけ広げる
+    max_half_extents: Vec2,
+}
+
+/*
+ * Resource
+ */
+// プレイエリアを一定サイズのセルに区切り、判定の中心があるセルに登録する。
+// 毎フレーム作り直し、近くのセルにあるものだけを詳細判定する
+pub struct SpatialHash {
+    min: Vec2,
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    layers: Vec<Layer>,
+}
+
+impl SpatialHash {
+    pub fn new(play_area: &PlayAreaDescriptor, cell_size: f32) -> Self {
+        let columns = (play_area.width / cell_size).ceil().max(1.) as usize;
+        let rows = (play_area.height / cell_size).ceil().max(1.) as usize;
+        let layers = (0..LAYER_COUNT)
+            .map(|_| Layer {
+                cells: (0..columns * rows).map(|_| Vec::new()).collect(),
+                max_half_extents: Vec2::ZERO,
+            })
+            .collect();
+
+        Self {
+            min: Vec2::new(play_area.min_x(), play_area.min_y()),
+            cell_size,
+            columns,
+            rows,
+            layers,
+        }
+    }
+
+    // セルの確保済みの領域は使い回す
+    pub fn clear(&mut self) {
+        for layer in self.layers.iter_mut() {
+            for cell in layer.cells.iter_mut() {
+                cell.clear();
+            }
+            layer.max_half_extents = Vec2::ZERO;
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        layer: CollisionLayer,
+        entity: Entity,
+        position: Vec3,
+        hitbox: Hitbox,
+    ) {
+        let (column, row) = self.cell_coord(hitbox.center(position));
+        let index = row * self.columns + column;
+        let layer = &mut self.layers[layer as usize];
+        layer.max_half_extents = layer.max_half_extents.max(hitbox.half_extents());
+        layer.cells[index].push(Entry {
+            entity,
+            position,
+            hitbox,
+        });
+    }
+
+    // 指定した判定と重なっているエンティティを返す
+    pub fn query<'a>(
+        &'a self,
+        layer: CollisionLayer,
+        position: Vec3,
+        hitbox: &'a Hitbox,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        let layer = &self.layers[layer as usize];
+        let center = hitbox.center(position);
+        let extents = hitbox.half_extents() + layer.max_half_extents;
+        let (min_column, min_row) = self.cell_coord(center - extents);
+        let (max_column, max_row) = self.cell_coord(center + extents);
+
+        (min_row..=max_row)
+            .flat_map(move |row| {
+                (min_column..=max_column).map(move |column| row * self.columns + column)
+            })
+            .flat_map(move |index| layer.cells[index].iter())
+            .filter(move |entry| hitbox.intersects(position, &entry.hitbox, entry.position))
+            .map(|entry| entry.entity)
+    }
+
+    // プレイエリア外はいちばん端のセルに含める
+    fn cell_coord(&self, point: Vec2) -> (usize, usize) {
+        let local = (point - self.min) / self.cell_size;
+        let column = (local.x.floor().max(0.) as usize).min(self.columns - 1);
+        let row = (local.y.floor().max(0.) as usize).min(self.rows - 1);
+        (column, row)
+    }
+}
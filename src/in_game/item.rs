@@ -0,0 +1,333 @@
+use super::extend::ExtendEvent;
+use super::game_frame::GameFrame;
+use super::system_label::GameSystemLabel;
+use crate::app_state::AppState;
+use crate::in_game::hitbox::{CollisionLayer, Hitbox, SpatialHash};
+use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::in_game::player::{Player, PLAYER_SIZE};
+use crate::in_game::scoreboard::Score;
+use bevy::prelude::*;
+use std::str::FromStr;
+
+const ITEM_SIZE: f32 = 12.0;
+const SMALL_ITEM_SIZE: f32 = 8.0;
+const BIG_ITEM_SIZE: f32 = 16.0;
+// 出現時に上に跳ねる速さ。落下は重力で加速し、最大落下速度で止まる
+const ITEM_POP_SPEED: f32 = 2.0;
+const ITEM_GRAVITY: f32 = 0.08;
+const ITEM_MAX_FALL_SPEED: f32 = 2.0;
+// 複数落とすときの横方向の広がりと、その減衰
+const ITEM_SCATTER_SPEED: f32 = 0.6;
+const ITEM_SCATTER_DRAG: f32 = 0.95;
+const ITEM_HOMING_SPEED: f32 = 8.0;
+const CANCEL_SCORE_ITEM_SIZE: f32 = 5.0;
+const CANCEL_SCORE_ITEM_SCORE: u128 = 10;
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ItemConfig>()
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(auto_collect_item_system)
+                    .with_system(move_item_system.after(auto_collect_item_system))
+                    .with_system(collect_item_system.after(GameSystemLabel::CollisionBroadphase))
+                    .with_system(despawn_item_go_outside_system),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+pub struct ItemConfig {
+    // 回収ラインのプレイエリア上端からの距離。自機がこれより上にいると画面上のアイテムを全て引き寄せる
+    pub collection_line_offset: f32,
+    // プレイエリアの下端で拾ったときの得点の割合。回収ラインに近づくほど1に近づく
+    pub min_score_rate: f32,
+    pub small_score: u128,
+    pub big_score: u128,
+}
+
+impl Default for ItemConfig {
+    fn default() -> Self {
+        Self {
+            collection_line_offset: 175.,
+            min_score_rate: 0.2,
+            small_score: 100,
+            big_score: 1000,
+        }
+    }
+}
+
+impl ItemConfig {
+    pub fn collection_line_y(&self, play_area: &PlayAreaDescriptor) -> f32 {
+        play_area.max_y() - self.collection_line_offset
+    }
+
+    // 拾った高さによる得点の割合
+    fn score_rate(&self, y: f32, play_area: &PlayAreaDescriptor) -> f32 {
+        let line_y = self.collection_line_y(play_area);
+        if y >= line_y {
+            return 1.;
+        }
+        let t = ((y - play_area.min_y()) / (line_y - play_area.min_y())).clamp(0., 1.);
+        self.min_score_rate + (1. - self.min_score_rate) * t
+    }
+
+    fn item_score(&self, base_score: u128, rate: f32) -> u128 {
+        // 端数が出ないように10点単位に丸める
+        ((base_score as f32 * rate / 10.).round() as u128) * 10
+    }
+}
+
+/*
+ * Component
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    SmallScore,
+    BigScore,
+    Power,
+    Bomb,
+    Extend,
+    // 弾消しで出る、自機に飛んでくる得点アイテム
+    CancelScore,
+}
+
+impl FromStr for ItemKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "small_score" => Ok(Self::SmallScore),
+            "big_score" => Ok(Self::BigScore),
+            "power" => Ok(Self::Power),
+            "bomb" => Ok(Self::Bomb),
+            "extend" => Ok(Self::Extend),
+            _ => Err(anyhow::anyhow!("Unsupported item kind: {}", s)),
+        }
+    }
+}
+
+impl ItemKind {
+    fn color(&self) -> Color {
+        match self {
+            Self::SmallScore | Self::BigScore => Color::rgb(0.3, 0.5, 1.0),
+            Self::Power => Color::rgb(1.0, 0.3, 0.3),
+            Self::Bomb => Color::rgb(1.0, 0.5, 1.0),
+            Self::Extend => Color::rgb(0.3, 1.0, 0.3),
+            Self::CancelScore => Color::rgb(1.0, 1.0, 0.6),
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            Self::SmallScore | Self::Power => SMALL_ITEM_SIZE,
+            Self::BigScore => BIG_ITEM_SIZE,
+            Self::Bomb | Self::Extend => ITEM_SIZE,
+            Self::CancelScore => CANCEL_SCORE_ITEM_SIZE,
+        }
+    }
+
+    fn is_homing(&self) -> bool {
+        matches!(self, Self::CancelScore)
+    }
+}
+
+#[derive(Component)]
+pub struct Item {
+    pub kind: ItemKind,
+    velocity: Vec3,
+}
+
+// 落下せずに自機に向かって飛んでいくアイテム。回収ラインで引き寄せたものは高さに関係なく満点になる
+#[derive(Component)]
+pub struct Homing;
+
+/*
+ * System
+ */
+fn cleanup(mut commands: Commands, query: Query<Entity, With<Item>>) {
+    for e in query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+// 自機が回収ラインより上にいる間、画面上のアイテムを引き寄せる
+fn auto_collect_item_system(
+    mut commands: Commands,
+    config: Res<ItemConfig>,
+    play_area: Res<PlayAreaDescriptor>,
+    player_query: Query<(&Player, &Transform)>,
+    item_query: Query<Entity, (With<Item>, Without<Homing>)>,
+) {
+    let (player, player_transform) = player_query.single();
+    if !player.is_controllable()
+        || player_transform.translation.y < config.collection_line_y(&play_area)
+    {
+        return;
+    }
+
+    for entity in item_query.iter() {
+        commands.entity(entity).insert(Homing);
+    }
+}
+
+fn move_item_system(
+    game_frame: Res<GameFrame>,
+    player_query: Query<&Transform, (With<Player>, Without<Item>)>,
+    mut query: Query<(&mut Item, &mut Transform, Option<&Homing>)>,
+) {
+    if !game_frame.is_changed() {
+        return;
+    }
+
+    let player_position = player_query.single().translation;
+    for (mut item, mut transform, homing) in query.iter_mut() {
+        if homing.is_some() {
+            let diff = player_position - transform.translation;
+            let distance = diff.length();
+            if distance <= ITEM_HOMING_SPEED {
+                transform.translation = player_position;
+            } else {
+                transform.translation += diff / distance * ITEM_HOMING_SPEED;
+            }
+        } else {
+            item.velocity.x *= ITEM_SCATTER_DRAG;
+            item.velocity.y = (item.velocity.y - ITEM_GRAVITY).max(-ITEM_MAX_FALL_SPEED);
+            transform.translation += item.velocity;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_item_system(
+    mut commands: Commands,
+    mut extend_events: EventWriter<ExtendEvent>,
+    mut score: ResMut<Score>,
+    config: Res<ItemConfig>,
+    play_area: Res<PlayAreaDescriptor>,
+    spatial_hash: Res<SpatialHash>,
+    item_query: Query<(&Item, Option<&Homing>)>,
+    player_query: Query<(&Player, &Transform)>,
+) {
+    let (player, player_transform) = player_query.single();
+    if !player.is_controllable() {
+        return;
+    }
+
+    // アイテムは被弾判定ではなく機体全体で拾う
+    let pickup_hitbox = Hitbox::circle(PLAYER_SIZE / 2.);
+    let height_rate = config.score_rate(player_transform.translation.y, &play_area);
+    for item_entity in spatial_hash.query(
+        CollisionLayer::Item,
+        player_transform.translation,
+        &pickup_hitbox,
+    ) {
+        let (item, homing) = match item_query.get(item_entity) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+
+        let rate = if homing.is_some() { 1. } else { height_rate };
+        match item.kind {
+            ItemKind::SmallScore => score.add_score(config.item_score(config.small_score, rate)),
+            ItemKind::BigScore => score.add_score(config.item_score(config.big_score, rate)),
+            ItemKind::Power => {
+                // パワーが最大のときは得点になる
+                if !score.add_power() {
+                    score.add_score(config.item_score(config.small_score, rate));
+                }
+            }
+            ItemKind::Bomb => score.add_bomb(),
+            ItemKind::Extend => extend_events.send(ExtendEvent),
+            ItemKind::CancelScore => score.add_score(CANCEL_SCORE_ITEM_SCORE),
+        }
+        commands.entity(item_entity).despawn();
+    }
+}
+
+fn despawn_item_go_outside_system(
+    play_area: Res<PlayAreaDescriptor>,
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), With<Item>>,
+) {
+    for (entity, transform) in query.iter() {
+        if transform.translation.y + ITEM_SIZE < play_area.min_y() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/*
+ * Utils
+ */
+// 撃破した敵のアイテムを、少し跳ねさせて横に散らしながら落とす
+pub fn spawn_drop_items(commands: &mut Commands, kinds: &[ItemKind], position: Vec3) {
+    let center = (kinds.len() as f32 - 1.) / 2.;
+    for (i, kind) in kinds.iter().enumerate() {
+        let velocity = Vec3::new((i as f32 - center) * ITEM_SCATTER_SPEED, ITEM_POP_SPEED, 0.);
+        spawn_item_with_velocity(commands, *kind, position, velocity);
+    }
+}
+
+pub fn spawn_item(commands: &mut Commands, kind: ItemKind, position: Vec3) {
+    spawn_item_with_velocity(commands, kind, position, Vec3::ZERO);
+}
+
+fn spawn_item_with_velocity(
+    commands: &mut Commands,
+    kind: ItemKind,
+    position: Vec3,
+    velocity: Vec3,
+) {
+    let size = kind.size();
+    let mut item = commands.spawn_bundle(SpriteBundle {
+        transform: Transform {
+            translation: position,
+            scale: Vec3::new(size, size, size),
+            ..Default::default()
+        },
+        sprite: Sprite {
+            color: kind.color(),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    item.insert(Item { kind, velocity })
+        .insert(Hitbox::circle(size / 2.));
+    if kind.is_homing() {
+        item.insert(Homing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_rate_rises_toward_collection_line() {
+        let config = ItemConfig::default();
+        let play_area = PlayAreaDescriptor::default();
+        let line_y = config.collection_line_y(&play_area);
+
+        assert_eq!(config.score_rate(play_area.min_y(), &play_area), 0.2);
+        // 下端より下でも最低の割合になる
+        assert_eq!(config.score_rate(play_area.min_y() - 10., &play_area), 0.2);
+        let middle_y = (play_area.min_y() + line_y) / 2.;
+        assert!((config.score_rate(middle_y, &play_area) - 0.6).abs() < 1e-5);
+        assert_eq!(config.score_rate(line_y, &play_area), 1.);
+        assert_eq!(config.score_rate(play_area.max_y(), &play_area), 1.);
+    }
+
+    #[test]
+    fn item_score_is_rounded_to_tens() {
+        let config = ItemConfig::default();
+        assert_eq!(config.item_score(config.small_score, 1.), 100);
+        assert_eq!(config.item_score(config.small_score, 0.2), 20);
+        assert_eq!(config.item_score(config.big_score, 0.637), 640);
+    }
+}
@@ -0,0 +1,213 @@
+use super::retry::retry;
+use super::system_label::GameSystemLabel;
+use crate::app_state::AppState;
+use crate::input_action::InputAction;
+use crate::menu_navigation::MenuSelectEvent;
+use crate::menu_widget::{selected_items, spawn_menu};
+use bevy::prelude::*;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        // InGameの上にPausedを積むので、InGameのシステムは止まり、戻ると続きから動く
+        // ミスやボス撃破と同じフレームで押されたときはそちらの画面遷移を優先する
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(pause_by_keyboard_system.after(GameSystemLabel::StateTransition)),
+        )
+        .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(setup))
+        .add_system_set(
+            SystemSet::on_update(AppState::Paused)
+                .with_system(resume_by_keyboard_system)
+                .with_system(pause_menu_system),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::Paused).with_system(cleanup));
+    }
+}
+
+struct PauseMenuData {
+    overlay_entity: Entity,
+}
+
+#[derive(Component, Clone, Copy)]
+enum PauseMenuButton {
+    Resume,
+    Retry,
+    ReturnToTitle,
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let overlay_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    "PAUSE",
+                    TextStyle {
+                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                        font_size: 40.0,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                ),
+                style: Style {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        })
+        .id();
+    let menu_entity = spawn_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        &[
+            (PauseMenuButton::Resume, "Resume".to_string()),
+            (PauseMenuButton::Retry, "Retry".to_string()),
+            (
+                PauseMenuButton::ReturnToTitle,
+                "Return to title".to_string(),
+            ),
+        ],
+    );
+    commands.entity(overlay_entity).add_child(menu_entity);
+    commands.insert_resource(PauseMenuData { overlay_entity });
+}
+
+fn cleanup(mut commands: Commands, menu_data: Res<PauseMenuData>) {
+    commands
+        .entity(menu_data.overlay_entity)
+        .despawn_recursive();
+    commands.remove_resource::<PauseMenuData>();
+}
+
+// 状態が切り替わった同じフレームで、積んだ側や戻った側が同じキー入力を拾わないように消しておく
+fn pause_by_keyboard_system(
+    mut action_input: ResMut<Input<InputAction>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if action_input.clear_just_pressed(InputAction::Pause) {
+        let _ = state.push(AppState::Paused);
+    }
+}
+
+fn resume_by_keyboard_system(
+    mut action_input: ResMut<Input<InputAction>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if action_input.clear_just_pressed(InputAction::Pause) {
+        // メニューの選択と重なったときは先に受け付けた方を優先する
+        let _ = state.pop();
+    }
+}
+
+fn pause_menu_system(
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    button_query: Query<&PauseMenuButton>,
+) {
+    for (_, button) in selected_items(&mut select_events, &button_query) {
+        // replaceはPausedを抜けてからInGameも抜けるので、ゲーム中の後片付けが走る
+        // Escと同じフレームで押されたときは先に受け付けた方を優先する
+        let _ = match button {
+            PauseMenuButton::Resume => state.pop(),
+            PauseMenuButton::Retry => retry(&mut state),
+            PauseMenuButton::ReturnToTitle => state.replace(AppState::Menu),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct InGameExits(u32);
+
+    fn count_exit(mut exits: ResMut<InGameExits>) {
+        exits.0 += 1;
+    }
+
+    fn setup_world() -> (World, SystemStage) {
+        let mut world = World::new();
+        world.insert_resource(State::new(AppState::InGame));
+        world.init_resource::<InGameExits>();
+        world.insert_resource(Input::<InputAction>::default());
+        let mut driver = SystemStage::single_threaded()
+            .with_system_set(State::<AppState>::get_driver())
+            .with_system_set(SystemSet::on_exit(AppState::InGame).with_system(count_exit));
+        driver.run(&mut world);
+        (world, driver)
+    }
+
+    fn press_pause(world: &mut World) {
+        let mut input = world.resource_mut::<Input<InputAction>>();
+        input.reset(InputAction::Pause);
+        input.press(InputAction::Pause);
+    }
+
+    #[test]
+    fn pause_and_resume_keep_in_game() {
+        let (mut world, mut driver) = setup_world();
+
+        press_pause(&mut world);
+        SystemStage::single_threaded()
+            .with_system(pause_by_keyboard_system)
+            .run(&mut world);
+        driver.run(&mut world);
+        let state = world.resource::<State<AppState>>();
+        assert_eq!(*state.current(), AppState::Paused);
+        assert_eq!(state.inactives(), &[AppState::InGame]);
+
+        press_pause(&mut world);
+        SystemStage::single_threaded()
+            .with_system(resume_by_keyboard_system)
+            .run(&mut world);
+        driver.run(&mut world);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::InGame
+        );
+        // 積んで戻っただけなので、ゲーム中の後片付けは走らない
+        assert_eq!(world.resource::<InGameExits>().0, 0);
+    }
+
+    #[test]
+    fn pause_yields_to_game_driven_transition() {
+        let (mut world, mut driver) = setup_world();
+        // ミスと同じフレームでポーズが押された場合
+        world
+            .resource_mut::<State<AppState>>()
+            .push(AppState::Continue)
+            .unwrap();
+        press_pause(&mut world);
+        SystemStage::single_threaded()
+            .with_system(pause_by_keyboard_system)
+            .run(&mut world);
+        driver.run(&mut world);
+
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::Continue
+        );
+        // 押されたポーズは消費されていて、次の画面に持ち越さない
+        assert!(!world
+            .resource::<Input<InputAction>>()
+            .just_pressed(InputAction::Pause));
+    }
+}
@@ -10,10 +10,16 @@ const PLAY_AREA_HEIGHT: f32 = WINDOW_HEIGHT;
 
 impl Plugin for PlayAreaPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayAreaDescriptor {
+        app.init_resource::<PlayAreaDescriptor>();
+    }
+}
+
+impl Default for PlayAreaDescriptor {
+    fn default() -> Self {
+        Self {
             width: PLAY_AREA_WIDTH,
             height: PLAY_AREA_HEIGHT,
             origin: Vec3::new(-WINDOW_WIDTH / 2. + PLAY_AREA_WIDTH / 2., 0., 0.),
-        });
+        }
     }
 }
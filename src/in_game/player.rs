@@ -1,24 +1,49 @@
 use crate::app_state::AppState;
+use crate::in_game::hitbox::Hitbox;
 use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::in_game::system_label::GameSystemLabel;
+use crate::input_action::InputAction;
+use crate::settings::{ControlMode, Settings};
 use bevy::prelude::*;
 
-const PLAYER_SIZE: f32 = 30.0;
+pub const PLAYER_SIZE: f32 = 30.0;
+const PLAYER_HITBOX_RADIUS: f32 = 2.0;
+// 1フレームに移動する距離
+const PLAYER_SPEED: f32 = 1.0;
+// 低速移動中の速度の倍率
+const FOCUS_SPEED_RATE: f32 = 0.5;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup))
-            .add_system_set(
-                SystemSet::on_update(AppState::InGame).with_system(move_player_by_keyboard_system),
-            )
-            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
+        // 弾幕のプレビューでは被弾しない自機として使う
+        for state in [AppState::InGame, AppState::BarragePreview] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(setup))
+                .add_system_set(
+                    SystemSet::on_update(state.clone())
+                        .with_system(
+                            move_player_by_keyboard_system.label(GameSystemLabel::PlayerMovement),
+                        )
+                        .with_system(
+                            move_player_by_pointer_system.label(GameSystemLabel::PlayerMovement),
+                        )
+                        .with_system(display_hitbox_marker_system),
+                )
+                .add_system_set(SystemSet::on_exit(state).with_system(cleanup));
+        }
     }
 }
 
 pub enum PlayerState {
     // 通常状態
     Normal,
+    // 被弾直後で、喰らいボムを受け付けている状態
+    Hit { rest_frame: i32 },
+    // 撃墜されて画面から消えている状態
+    Dead { rest_frame: i32 },
+    // 画面下から復帰中の状態
+    Respawning { rest_frame: i32 },
     // 被弾して無敵状態
     DamegedInvincible { rest_frame: i32 },
 }
@@ -36,6 +61,29 @@ impl Default for Player {
     }
 }
 
+impl Player {
+    // 移動やショットの操作を受け付けるかどうか
+    pub fn is_controllable(&self) -> bool {
+        matches!(
+            self.state,
+            PlayerState::Normal | PlayerState::DamegedInvincible { .. }
+        )
+    }
+
+    // ゲーム開始時、復帰時の自機の位置
+    pub fn initial_position(play_area: &PlayAreaDescriptor) -> Vec3 {
+        Vec3::new(
+            play_area.origin.x - PLAYER_SIZE / 2.0,
+            play_area.origin.y - (play_area.height / 2. - PLAYER_SIZE * 3.0 - PLAYER_SIZE / 2.0),
+            0.0,
+        )
+    }
+}
+
+// 設定で当たり判定を表示するときの印
+#[derive(Component)]
+struct HitboxMarker;
+
 pub struct PlayerAssets {
     pub normal_state_handle: Handle<Image>,
     pub damaged_state_handle: Handle<Image>,
@@ -45,6 +93,7 @@ fn setup(
     play_area: Res<PlayAreaDescriptor>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
 ) {
     let player_assets = PlayerAssets {
         normal_state_handle: asset_server.load("images/rocket.png"),
@@ -54,12 +103,7 @@ fn setup(
     commands
         .spawn_bundle(SpriteBundle {
             transform: Transform {
-                translation: Vec3::new(
-                    play_area.origin.x - PLAYER_SIZE / 2.0,
-                    play_area.origin.y
-                        - (play_area.height / 2. - PLAYER_SIZE * 3.0 - PLAYER_SIZE / 2.0),
-                    0.0,
-                ),
+                translation: Player::initial_position(&play_area),
                 scale: Vec3::new(PLAYER_SIZE, PLAYER_SIZE, PLAYER_SIZE),
                 ..Default::default()
             },
@@ -70,7 +114,30 @@ fn setup(
             texture: player_assets.normal_state_handle.clone(),
             ..Default::default()
         })
-        .insert(Player::default());
+        .insert(Player::default())
+        .insert(Hitbox::circle(PLAYER_HITBOX_RADIUS))
+        .with_children(|parent| {
+            // 自機のスケールが掛かるので、その分小さくしておく
+            let marker_scale = PLAYER_HITBOX_RADIUS * 2.0 / PLAYER_SIZE;
+            parent
+                .spawn_bundle(SpriteBundle {
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, 0.1),
+                        scale: Vec3::new(marker_scale, marker_scale, 1.0),
+                        ..Default::default()
+                    },
+                    sprite: Sprite {
+                        color: Color::rgb(1.0, 0.2, 0.2),
+                        custom_size: Some(Vec2::new(1.0, 1.0)),
+                        ..Default::default()
+                    },
+                    visibility: Visibility {
+                        is_visible: settings.show_hitbox,
+                    },
+                    ..Default::default()
+                })
+                .insert(HitboxMarker);
+        });
 
     commands.insert_resource(player_assets);
 }
@@ -82,35 +149,119 @@ fn cleanup(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
 
 fn move_player_by_keyboard_system(
     play_area: Res<PlayAreaDescriptor>,
-    keyboard_input: Res<Input<KeyCode>>,
+    action_input: Res<Input<InputAction>>,
+    settings: Res<Settings>,
     mut query: Query<(&Player, &mut Transform)>,
 ) {
-    let (_, mut transform) = query.single_mut();
+    if settings.control_mode != ControlMode::Keyboard {
+        return;
+    }
+    let (player, mut transform) = query.single_mut();
+    if !player.is_controllable() {
+        return;
+    }
 
     // 斜め移動も考慮して比率計算
     let move_ratio;
-    if (keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::D))
-        && (keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::S))
+    if action_input.any_pressed([InputAction::MoveLeft, InputAction::MoveRight])
+        && action_input.any_pressed([InputAction::MoveUp, InputAction::MoveDown])
     {
         move_ratio = 0.71;
     } else {
         move_ratio = 1.0;
     }
 
-    let move_dist = 1.0;
-    if keyboard_input.pressed(KeyCode::A) {
+    let move_dist = if action_input.pressed(InputAction::Focus) {
+        PLAYER_SPEED * FOCUS_SPEED_RATE
+    } else {
+        PLAYER_SPEED
+    };
+    if action_input.pressed(InputAction::MoveLeft) {
         transform.translation.x -= move_dist * move_ratio;
     }
-    if keyboard_input.pressed(KeyCode::D) {
+    if action_input.pressed(InputAction::MoveRight) {
         transform.translation.x += move_dist * move_ratio;
     }
-    if keyboard_input.pressed(KeyCode::W) {
+    if action_input.pressed(InputAction::MoveUp) {
         transform.translation.y += move_dist * move_ratio;
     }
-    if keyboard_input.pressed(KeyCode::S) {
+    if action_input.pressed(InputAction::MoveDown) {
         transform.translation.y -= move_dist * move_ratio;
     }
 
+    clamp_to_play_area(&play_area, &mut transform);
+}
+
+// ポインタの位置に向かって、キーボードで動かすときと同じ速さで移動する
+fn move_player_by_pointer_system(
+    play_area: Res<PlayAreaDescriptor>,
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut query: Query<(&Player, &mut Transform)>,
+) {
+    if settings.control_mode != ControlMode::Pointer {
+        return;
+    }
+    let (player, mut transform) = query.single_mut();
+    if !player.is_controllable() {
+        return;
+    }
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    // ウィンドウの外にあるときは動かない
+    let cursor_position = match window.cursor_position() {
+        Some(position) => position,
+        None => return,
+    };
+    let (camera, camera_transform) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let target = cursor_to_world(
+        cursor_position,
+        Vec2::new(window.width(), window.height()),
+        camera_transform,
+        camera.projection_matrix(),
+    )
+    .extend(transform.translation.z);
+    let diff = target - transform.translation;
+    transform.translation += diff.clamp_length_max(PLAYER_SPEED);
+
+    clamp_to_play_area(&play_area, &mut transform);
+}
+
+fn display_hitbox_marker_system(
+    settings: Res<Settings>,
+    mut query: Query<&mut Visibility, With<HitboxMarker>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut visibility in query.iter_mut() {
+        visibility.is_visible = settings.show_hitbox;
+    }
+}
+
+/*
+ * Utils
+ */
+// ウィンドウ上の位置(左下が原点)を、カメラの位置と投影からワールド座標に直す
+fn cursor_to_world(
+    cursor_position: Vec2,
+    window_size: Vec2,
+    camera_transform: &GlobalTransform,
+    projection_matrix: Mat4,
+) -> Vec2 {
+    let ndc = cursor_position / window_size * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * projection_matrix.inverse();
+    ndc_to_world.project_point3(ndc.extend(0.0)).truncate()
+}
+
+fn clamp_to_play_area(play_area: &PlayAreaDescriptor, transform: &mut Transform) {
     transform.translation.x = transform
         .translation
         .x
@@ -123,3 +274,67 @@ fn move_player_by_keyboard_system(
         .min(play_area.max_y() - transform.scale.y / 2.0)
         .max(play_area.min_y() + transform.scale.y / 2.0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::camera::CameraProjection;
+
+    fn projection_matrix(window_size: Vec2, scale: f32) -> Mat4 {
+        let mut projection = OrthographicProjection {
+            scale,
+            ..Default::default()
+        };
+        projection.update(window_size.x, window_size.y);
+        projection.get_projection_matrix()
+    }
+
+    #[test]
+    fn converts_cursor_with_camera() {
+        let window_size = Vec2::new(700.0, 700.0);
+        let camera_transform = GlobalTransform::from_xyz(0.0, 0.0, 999.9);
+        let to_world = |cursor, transform: &GlobalTransform, scale| {
+            cursor_to_world(
+                cursor,
+                window_size,
+                transform,
+                projection_matrix(window_size, scale),
+            )
+        };
+
+        assert!(
+            to_world(Vec2::new(350.0, 350.0), &camera_transform, 1.0).abs_diff_eq(Vec2::ZERO, 1e-3)
+        );
+        assert!(to_world(Vec2::ZERO, &camera_transform, 1.0)
+            .abs_diff_eq(Vec2::new(-350.0, -350.0), 1e-3));
+
+        // カメラが動いていればその分ずれ、拡大していればその倍率で広がる
+        let moved = GlobalTransform::from_xyz(100.0, -50.0, 999.9);
+        assert!(to_world(Vec2::new(350.0, 350.0), &moved, 1.0)
+            .abs_diff_eq(Vec2::new(100.0, -50.0), 1e-3));
+        assert!(to_world(Vec2::new(700.0, 0.0), &camera_transform, 2.0)
+            .abs_diff_eq(Vec2::new(700.0, -700.0), 1e-3));
+    }
+
+    #[test]
+    fn clamps_to_play_area() {
+        let play_area = PlayAreaDescriptor::default();
+        let mut transform = Transform {
+            translation: Vec3::new(play_area.max_x() + 100.0, play_area.min_y() - 100.0, 0.0),
+            scale: Vec3::new(PLAYER_SIZE, PLAYER_SIZE, PLAYER_SIZE),
+            ..Default::default()
+        };
+        clamp_to_play_area(&play_area, &mut transform);
+        assert_eq!(transform.translation.x, play_area.max_x() - PLAYER_SIZE);
+        assert_eq!(
+            transform.translation.y,
+            play_area.min_y() + PLAYER_SIZE / 2.0
+        );
+
+        // プレイエリアの中ならそのまま
+        let inside = Player::initial_position(&play_area);
+        transform.translation = inside;
+        clamp_to_play_area(&play_area, &mut transform);
+        assert_eq!(transform.translation, inside);
+    }
+}
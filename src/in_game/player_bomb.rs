@@ -0,0 +1,114 @@
+use super::enemy::{CancelBulletsEvent, CancelTarget};
+use super::explosion::spawn_explosion;
+use super::player_death::PlayerDeathConfig;
+use crate::app_state::AppState;
+use crate::in_game::player::{Player, PlayerState};
+use crate::in_game::scoreboard::Score;
+use crate::input_action::InputAction;
+use bevy::prelude::*;
+
+const BOMB_EFFECT_SIZE: f32 = 600.0;
+const BOMB_EFFECT_FRAMES: i32 = 40;
+
+pub struct PlayerBombPlugin;
+
+impl Plugin for PlayerBombPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::InGame).with_system(use_bomb_by_keyboard_system),
+        );
+    }
+}
+
+/*
+ * System
+ */
+fn use_bomb_by_keyboard_system(
+    mut commands: Commands,
+    action_input: Res<Input<InputAction>>,
+    death_config: Res<PlayerDeathConfig>,
+    mut score: ResMut<Score>,
+    mut cancel_events: EventWriter<CancelBulletsEvent>,
+    mut player_query: Query<(&mut Player, &Transform)>,
+) {
+    if !action_input.just_pressed(InputAction::Bomb) {
+        return;
+    }
+
+    let (mut player, player_transform) = player_query.single_mut();
+    // 被弾直後(喰らいボム)でも使える
+    let can_use_bomb = player.is_controllable() || matches!(player.state, PlayerState::Hit { .. });
+    if !can_use_bomb || !score.use_bomb() {
+        return;
+    }
+
+    spawn_explosion(
+        &mut commands,
+        player_transform.translation,
+        Color::rgb(0.6, 0.8, 1.0),
+        BOMB_EFFECT_SIZE,
+        BOMB_EFFECT_FRAMES,
+    );
+    cancel_events.send(CancelBulletsEvent(CancelTarget::All));
+    player.state = PlayerState::DamegedInvincible {
+        rest_frame: death_config.invincible_frames,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+
+    fn bomb_world(player_state: PlayerState) -> (World, Entity) {
+        let mut world = World::new();
+        let mut action_input = Input::<InputAction>::default();
+        action_input.press(InputAction::Bomb);
+        world.insert_resource(action_input);
+        world.insert_resource(PlayerDeathConfig::default());
+        world.insert_resource(Score::new(3, 1));
+        world.init_resource::<Events<CancelBulletsEvent>>();
+        let player = world
+            .spawn()
+            .insert_bundle((
+                Player {
+                    state: player_state,
+                },
+                Transform::default(),
+            ))
+            .id();
+        (world, player)
+    }
+
+    #[test]
+    fn deathbomb_cancels_hit_within_window() {
+        let (mut world, player) = bomb_world(PlayerState::Hit { rest_frame: 3 });
+        SystemStage::single_threaded()
+            .with_system(use_bomb_by_keyboard_system)
+            .run(&mut world);
+
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::DamegedInvincible { .. }
+        ));
+        let mut score = world.resource_mut::<Score>();
+        assert!(!score.use_bomb());
+        // 被弾は取り消されたので残機は減らない
+        assert!(score.on_hit_enemy_bullet());
+        assert!(score.on_hit_enemy_bullet());
+    }
+
+    #[test]
+    fn cannot_bomb_after_being_shot_down() {
+        let (mut world, player) = bomb_world(PlayerState::Dead { rest_frame: 10 });
+        SystemStage::single_threaded()
+            .with_system(use_bomb_by_keyboard_system)
+            .run(&mut world);
+
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::Dead { .. }
+        ));
+        assert!(world.resource_mut::<Score>().use_bomb());
+    }
+}
@@ -0,0 +1,313 @@
+use super::continue_prompt::ContinueConfig;
+use super::enemy::{CancelBulletsEvent, CancelTarget};
+use super::explosion::spawn_explosion;
+use super::game_frame::GameFrame;
+use super::system_label::GameSystemLabel;
+use crate::app_state::AppState;
+use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::in_game::player::{Player, PlayerAssets, PlayerState, PLAYER_SIZE};
+use crate::in_game::scoreboard::Score;
+use crate::FPS;
+use bevy::prelude::*;
+
+pub struct PlayerDeathPlugin;
+
+impl Plugin for PlayerDeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerDeathConfig>().add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(update_player_death_system.label(GameSystemLabel::StateTransition))
+                .before(GameSystemLabel::GameFrameUpdate),
+        );
+    }
+}
+
+/*
+ * Resource
+ */
+// 被弾から復帰までの各フェーズの長さ。アプリ側で上書きして調整できる
+pub struct PlayerDeathConfig {
+    // 被弾後にボムを押すと被弾を取り消せるフレーム数
+    pub deathbomb_frames: i32,
+    // 爆発してから復帰を始めるまでのフレーム数
+    pub dead_frames: i32,
+    // 画面下から定位置まで移動するフレーム数
+    pub respawn_frames: i32,
+    // 復帰後の無敵フレーム数
+    pub invincible_frames: i32,
+    // 復帰時に弾を消す、復帰位置からの半径
+    pub bullet_clear_radius: f32,
+}
+
+impl Default for PlayerDeathConfig {
+    fn default() -> Self {
+        Self {
+            deathbomb_frames: 8,
+            dead_frames: FPS as i32,
+            respawn_frames: (FPS * 0.75) as i32,
+            invincible_frames: (FPS * 2.) as i32,
+            bullet_clear_radius: 150.,
+        }
+    }
+}
+
+/*
+ * System
+ */
+#[allow(clippy::too_many_arguments)]
+fn update_player_death_system(
+    game_frame: Res<GameFrame>,
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    config: Res<PlayerDeathConfig>,
+    continue_config: Res<ContinueConfig>,
+    play_area: Res<PlayAreaDescriptor>,
+    player_assets: Res<PlayerAssets>,
+    mut score: ResMut<Score>,
+    mut cancel_events: EventWriter<CancelBulletsEvent>,
+    mut player_query: Query<(
+        &mut Player,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<Image>,
+    )>,
+) {
+    if !game_frame.is_changed() {
+        return;
+    }
+
+    let (mut player, mut transform, mut visibility, mut sprite_handle) = player_query.single_mut();
+    match player.state {
+        PlayerState::Hit { ref mut rest_frame } => {
+            if *rest_frame > 0 {
+                *rest_frame -= 1;
+                return;
+            }
+
+            // 喰らいボムが間に合わなかったので撃墜
+            score.on_hit_enemy_bullet();
+            score.lose_power();
+            spawn_explosion(
+                &mut commands,
+                transform.translation,
+                Color::rgb(1.0, 0.6, 0.2),
+                PLAYER_SIZE * 4.,
+                config.dead_frames,
+            );
+            visibility.is_visible = false;
+            player.state = PlayerState::Dead {
+                rest_frame: config.dead_frames,
+            };
+        }
+        PlayerState::Dead { ref mut rest_frame } => {
+            if *rest_frame > 0 {
+                *rest_frame -= 1;
+                return;
+            }
+
+            if !score.has_stock() {
+                // コンティニューできる間はInGameを止めて確認し、コンティニューすると続きから復帰する
+                if score.continue_count() < continue_config.max_continues {
+                    state.push(AppState::Continue).unwrap();
+                } else {
+                    state.set(AppState::GameOver).unwrap();
+                }
+                return;
+            }
+
+            // 復帰地点周辺の弾を消してから画面下に出現させる
+            let respawn_position = Player::initial_position(&play_area);
+            cancel_events.send(CancelBulletsEvent(CancelTarget::Within {
+                center: respawn_position,
+                radius: config.bullet_clear_radius,
+            }));
+            score.refill_bomb();
+            transform.translation = respawn_start_position(&play_area, respawn_position);
+            visibility.is_visible = true;
+            *sprite_handle = player_assets.damaged_state_handle.clone();
+            player.state = PlayerState::Respawning {
+                rest_frame: config.respawn_frames,
+            };
+        }
+        PlayerState::Respawning { ref mut rest_frame } => {
+            if *rest_frame > 0 {
+                *rest_frame -= 1;
+            }
+
+            let start = respawn_start_position(&play_area, Player::initial_position(&play_area));
+            let end = Player::initial_position(&play_area);
+            let progress = 1.0 - *rest_frame as f32 / config.respawn_frames.max(1) as f32;
+            // 減速しながら定位置に戻る
+            let eased = 1.0 - (1.0 - progress) * (1.0 - progress);
+            transform.translation = start.lerp(end, eased);
+
+            if *rest_frame == 0 {
+                player.state = PlayerState::DamegedInvincible {
+                    rest_frame: config.invincible_frames,
+                };
+            }
+        }
+        PlayerState::Normal | PlayerState::DamegedInvincible { .. } => {}
+    }
+}
+
+/*
+ * Utils
+ */
+fn respawn_start_position(play_area: &PlayAreaDescriptor, respawn_position: Vec3) -> Vec3 {
+    Vec3::new(
+        respawn_position.x,
+        play_area.min_y() - PLAYER_SIZE,
+        respawn_position.z,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+
+    fn setup_world(player_state: PlayerState, player_stock: i32) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(GameFrame(0));
+        world.insert_resource(State::new(AppState::InGame));
+        world.insert_resource(PlayerDeathConfig::default());
+        world.insert_resource(ContinueConfig::default());
+        world.insert_resource(PlayAreaDescriptor::default());
+        world.insert_resource(PlayerAssets {
+            normal_state_handle: Handle::default(),
+            damaged_state_handle: Handle::default(),
+        });
+        world.insert_resource(Score::new(player_stock, 2));
+        world.init_resource::<Events<CancelBulletsEvent>>();
+        // 最初の状態に入る遷移を済ませておく
+        SystemStage::single_threaded()
+            .with_system_set(State::<AppState>::get_driver())
+            .run(&mut world);
+        let player = world
+            .spawn()
+            .insert_bundle((
+                Player {
+                    state: player_state,
+                },
+                Transform::default(),
+                Visibility::default(),
+                Handle::<Image>::default(),
+            ))
+            .id();
+        (world, player)
+    }
+
+    // GameFrameが進んだフレームだけ処理されるので、1フレームずつ進めて実行する
+    fn run_frames(world: &mut World, stage: &mut SystemStage, frames: i32) {
+        for _ in 0..frames {
+            world.resource_mut::<GameFrame>().0 += 1;
+            stage.run(world);
+        }
+    }
+
+    fn death_stage() -> SystemStage {
+        SystemStage::single_threaded()
+            .with_system_set(State::<AppState>::get_driver())
+            .with_system(update_player_death_system)
+    }
+
+    #[test]
+    fn dies_after_deathbomb_window_and_respawns() {
+        let config = PlayerDeathConfig::default();
+        let (mut world, player) = setup_world(
+            PlayerState::Hit {
+                rest_frame: config.deathbomb_frames,
+            },
+            3,
+        );
+        let mut stage = death_stage();
+
+        // 受付時間の間はまだ撃墜されない
+        run_frames(&mut world, &mut stage, config.deathbomb_frames);
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::Hit { rest_frame: 0 }
+        ));
+        run_frames(&mut world, &mut stage, 1);
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::Dead { .. }
+        ));
+        assert!(!world.get::<Visibility>(player).unwrap().is_visible);
+        assert!(world.resource::<Score>().has_stock());
+
+        // 復帰するときは復帰地点の周りの弾を消す
+        run_frames(&mut world, &mut stage, config.dead_frames + 1);
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::Respawning { .. }
+        ));
+        let events = world.resource::<Events<CancelBulletsEvent>>();
+        let cancel = events.iter_current_update_events().next().unwrap();
+        assert!(matches!(
+            cancel.0,
+            CancelTarget::Within { radius, .. } if radius == config.bullet_clear_radius
+        ));
+
+        run_frames(&mut world, &mut stage, config.respawn_frames);
+        assert!(matches!(
+            world.get::<Player>(player).unwrap().state,
+            PlayerState::DamegedInvincible { .. }
+        ));
+        let play_area = PlayAreaDescriptor::default();
+        assert_eq!(
+            world.get::<Transform>(player).unwrap().translation,
+            Player::initial_position(&play_area)
+        );
+    }
+
+    #[test]
+    fn asks_to_continue_when_out_of_stock() {
+        let (mut world, _) = setup_world(PlayerState::Dead { rest_frame: 0 }, 1);
+        world.resource_mut::<Score>().on_hit_enemy_bullet();
+        let mut stage = death_stage();
+
+        run_frames(&mut world, &mut stage, 1);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::Continue
+        );
+    }
+
+    #[test]
+    fn game_over_after_continuing_up_to_limit() {
+        let (mut world, _) = setup_world(PlayerState::Dead { rest_frame: 0 }, 1);
+        {
+            let mut score = world.resource_mut::<Score>();
+            for _ in 0..ContinueConfig::default().max_continues {
+                score.continue_game();
+            }
+            score.on_hit_enemy_bullet();
+        }
+        let mut stage = death_stage();
+
+        run_frames(&mut world, &mut stage, 1);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::GameOver
+        );
+    }
+
+    #[test]
+    fn game_over_when_continues_are_used_up() {
+        let (mut world, _) = setup_world(PlayerState::Dead { rest_frame: 0 }, 1);
+        world.insert_resource(ContinueConfig {
+            max_continues: 0,
+            ..Default::default()
+        });
+        world.resource_mut::<Score>().on_hit_enemy_bullet();
+        let mut stage = death_stage();
+
+        run_frames(&mut world, &mut stage, 1);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::GameOver
+        );
+    }
+}
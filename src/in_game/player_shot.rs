@@ -1,9 +1,15 @@
 use super::player::Player;
 use crate::app_state::AppState;
+use crate::in_game::hitbox::Hitbox;
 use crate::in_game::play_area::PlayAreaDescriptor;
+use crate::in_game::scoreboard::Score;
+use crate::input_action::InputAction;
 use bevy::prelude::*;
 
 const BULLET_SIZE: f32 = 15.0;
+const BULLET_HITBOX_WIDTH: f32 = 6.0;
+// パワーで増える弾の横方向の間隔
+const BULLET_SPACING: f32 = 12.0;
 
 pub struct PlayerShotPlugin;
 
@@ -16,6 +22,10 @@ impl Plugin for PlayerShotPlugin {
                 .with_system(destroy_player_bullet_go_outside_system)
                 .with_system(move_player_bullet_system),
         )
+        .add_system_set(
+            SystemSet::on_resume(AppState::InGame)
+                .with_system(stop_player_shot_released_while_paused_system),
+        )
         .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
     }
 }
@@ -46,26 +56,30 @@ fn cleanup(mut commands: Commands, player_bullet_query: Query<Entity, With<Bulle
 
 fn shot_player_bullet_by_keyboard_system(
     mut commands: Commands,
-    keyboard_input: Res<Input<KeyCode>>,
+    action_input: Res<Input<InputAction>>,
+    score: Res<Score>,
     query: Query<(&Player, &Transform)>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if action_input.just_pressed(InputAction::Shot) {
         start_repeat_player_bullet_shot_timer(&mut commands);
-    } else if keyboard_input.just_released(KeyCode::Space) {
+    } else if action_input.just_released(InputAction::Shot) {
         stop_repeat_player_bullet_shot_timer(&mut commands);
         return;
     } else {
         return;
     }
 
-    let (_, transform) = query.single();
-    shot_player_bullet(commands, transform);
+    let (player, transform) = query.single();
+    if player.is_controllable() {
+        shot_player_bullet(commands, transform, score.player_power());
+    }
 }
 
 fn repeat_player_shot_by_timer_system(
     commands: Commands,
     time: Res<Time>,
     mut timer: Option<ResMut<ShotPlayerBulletTimer>>,
+    score: Res<Score>,
     player_query: Query<(&Player, &Transform)>,
 ) {
     if let Some(ref mut timer) = timer {
@@ -73,8 +87,20 @@ fn repeat_player_shot_by_timer_system(
             return;
         }
 
-        let (_, transform) = player_query.single();
-        shot_player_bullet(commands, transform);
+        let (player, transform) = player_query.single();
+        if player.is_controllable() {
+            shot_player_bullet(commands, transform, score.player_power());
+        }
+    }
+}
+
+// ポーズ中にショットのキーを離していたら連射を止める
+fn stop_player_shot_released_while_paused_system(
+    mut commands: Commands,
+    action_input: Res<Input<InputAction>>,
+) {
+    if !action_input.pressed(InputAction::Shot) {
+        stop_repeat_player_bullet_shot_timer(&mut commands);
     }
 }
 
@@ -108,23 +134,29 @@ fn stop_repeat_player_bullet_shot_timer(commands: &mut Commands) {
     commands.remove_resource::<ShotPlayerBulletTimer>();
 }
 
-fn shot_player_bullet(mut commands: Commands, player_transform: &Transform) {
-    commands
-        .spawn_bundle(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(
-                    player_transform.translation.x,
-                    player_transform.translation.y,
-                    0.0,
-                ),
-                scale: Vec3::new(BULLET_SIZE, BULLET_SIZE, BULLET_SIZE),
+// パワー1につき1発ずつ横に並べて撃つ
+fn shot_player_bullet(mut commands: Commands, player_transform: &Transform, power: i32) {
+    let center = power as f32 / 2.;
+    for i in 0..=power {
+        let offset_x = (i as f32 - center) * BULLET_SPACING;
+        commands
+            .spawn_bundle(SpriteBundle {
+                transform: Transform {
+                    translation: Vec3::new(
+                        player_transform.translation.x + offset_x,
+                        player_transform.translation.y,
+                        0.0,
+                    ),
+                    scale: Vec3::new(BULLET_SIZE, BULLET_SIZE, BULLET_SIZE),
+                    ..Default::default()
+                },
+                sprite: Sprite {
+                    color: Color::rgb(1.0, 1.0, 0.5),
+                    ..Default::default()
+                },
                 ..Default::default()
-            },
-            sprite: Sprite {
-                color: Color::rgb(1.0, 1.0, 0.5),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Bullet);
+            })
+            .insert(Bullet)
+            .insert(Hitbox::rect(Vec2::new(BULLET_HITBOX_WIDTH, BULLET_SIZE)));
+    }
 }
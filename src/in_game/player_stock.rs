@@ -1,16 +1,13 @@
-use super::enemy::Bullet as EnemyBullet;
+use super::enemy::{Bullet as EnemyBullet, Enemy};
 use super::game_frame::GameFrame;
+use super::player_death::PlayerDeathConfig;
 use super::system_label::GameSystemLabel;
 use crate::app_state::AppState;
+use crate::in_game::hitbox::{CollisionLayer, Hitbox, SpatialHash};
 use crate::in_game::player::Player;
 use crate::in_game::player::PlayerAssets;
 use crate::in_game::player::PlayerState;
-use crate::in_game::scoreboard::Score;
-use crate::FPS;
 use bevy::prelude::*;
-use bevy::sprite::collide_aabb::collide;
-
-const DAMAGED_INVINCIBLE_FRAME: i32 = (FPS * 2.) as i32;
 
 pub struct PlayerStockPlugin;
 
@@ -18,7 +15,8 @@ impl Plugin for PlayerStockPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(
             SystemSet::on_update(AppState::InGame)
-                .with_system(hit_enemy_bullet_system)
+                .with_system(hit_enemy_bullet_system.after(GameSystemLabel::CollisionBroadphase))
+                .with_system(hit_enemy_body_system.after(GameSystemLabel::CollisionBroadphase))
                 .with_system(decrease_damaged_invincible_frame_system)
                 .before(GameSystemLabel::GameFrameUpdate),
         );
@@ -26,44 +24,72 @@ impl Plugin for PlayerStockPlugin {
 }
 
 fn hit_enemy_bullet_system(
-    mut state: ResMut<State<AppState>>,
-    player_assets: Res<PlayerAssets>,
-    mut score: ResMut<Score>,
-    enemy_bullet_query: Query<&Transform, With<EnemyBullet>>,
-    mut player_query: Query<(&Transform, &mut Player, &mut Handle<Image>)>,
+    death_config: Res<PlayerDeathConfig>,
+    spatial_hash: Res<SpatialHash>,
+    bullet_query: Query<&EnemyBullet>,
+    mut player_query: Query<(&Transform, &Hitbox, &mut Player)>,
 ) {
-    let player_hit_area_size = Vec2::new(2., 2.);
-    let (player_transform, mut player, mut sprite_handle) = player_query.single_mut();
+    let (player_transform, player_hitbox, mut player) = player_query.single_mut();
     match player.state {
         PlayerState::Normal => {
-            for enemy_bullet_transform in enemy_bullet_query.iter() {
-                let collision = collide(
+            // 連続被弾はしないので、重なっている弾が一つでもあれば被弾
+            let collision = spatial_hash
+                .query(
+                    CollisionLayer::EnemyBullet,
                     player_transform.translation,
-                    player_hit_area_size,
-                    enemy_bullet_transform.translation,
-                    enemy_bullet_transform.scale.truncate(),
-                );
+                    player_hitbox,
+                )
+                // 同じフレームで既に消された弾や、プールで待機中の弾には当たらない
+                .any(|entity| {
+                    bullet_query
+                        .get(entity)
+                        .map(|bullet| !bullet.vanished && !bullet.released)
+                        .unwrap_or(false)
+                });
 
-                if collision.is_some() {
-                    if score.on_hit_enemy_bullet() {
-                        player.state = PlayerState::DamegedInvincible {
-                            rest_frame: DAMAGED_INVINCIBLE_FRAME,
-                        };
-                        *sprite_handle = player_assets.damaged_state_handle.clone();
-                    } else {
-                        state.set(AppState::GameOver).unwrap();
-                    }
-                    // 連続被弾はしない
-                    break;
-                }
+            if collision {
+                // 撃墜処理は喰らいボムの受付時間が過ぎてから行う
+                player.state = PlayerState::Hit {
+                    rest_frame: death_config.deathbomb_frames,
+                };
             }
         }
-        PlayerState::DamegedInvincible { .. } => {
-            // 被弾後の無敵時間中なので、被弾しない
+        _ => {
+            // 被弾処理中、または被弾後の無敵時間中なので、被弾しない
         }
     }
 }
 
+fn hit_enemy_body_system(
+    death_config: Res<PlayerDeathConfig>,
+    spatial_hash: Res<SpatialHash>,
+    enemy_query: Query<&Enemy>,
+    mut player_query: Query<(&Transform, &Hitbox, &mut Player)>,
+) {
+    let (player_transform, player_hitbox, mut player) = player_query.single_mut();
+    if !matches!(player.state, PlayerState::Normal) {
+        return;
+    }
+
+    let collision = spatial_hash
+        .query(
+            CollisionLayer::Enemy,
+            player_transform.translation,
+            player_hitbox,
+        )
+        .any(|entity| {
+            enemy_query
+                .get(entity)
+                .map(|enemy| enemy.body_collision)
+                .unwrap_or(false)
+        });
+    if collision {
+        player.state = PlayerState::Hit {
+            rest_frame: death_config.deathbomb_frames,
+        };
+    }
+}
+
 fn decrease_damaged_invincible_frame_system(
     game_frame: Res<GameFrame>,
     player_assets: Res<PlayerAssets>,
@@ -0,0 +1,130 @@
+use super::system_label::GameSystemLabel;
+use crate::app_state::AppState;
+use crate::input_action::InputAction;
+use bevy::ecs::schedule::StateError;
+use bevy::prelude::*;
+
+pub struct RetryPlugin;
+
+impl Plugin for RetryPlugin {
+    fn build(&self, app: &mut App) {
+        for state in [AppState::InGame, AppState::Paused] {
+            app.add_system_set(
+                SystemSet::on_update(state)
+                    .with_system(retry_by_keyboard_system.after(GameSystemLabel::StateTransition)),
+            );
+        }
+    }
+}
+
+// InGameを抜けてすぐに入り直す。各プラグインのcleanupとsetupが同じフレームで順に走り、
+// GameFrameやScore、敵の出現表、弾幕、画面上のエンティティが全て最初の状態に戻る
+pub fn retry(state: &mut State<AppState>) -> Result<(), StateError> {
+    if *state.current() == AppState::InGame {
+        state.restart()
+    } else {
+        // ポーズ中はPausedを抜けてからInGameを入り直す
+        state.replace(AppState::InGame)
+    }
+}
+
+fn retry_by_keyboard_system(
+    mut action_input: ResMut<Input<InputAction>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if action_input.clear_just_pressed(InputAction::Retry) {
+        // 他の画面遷移と重なったときはそちらを優先する
+        let _ = retry(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Transitions {
+        entered: u32,
+        exited: u32,
+    }
+
+    fn count_enter(mut transitions: ResMut<Transitions>) {
+        transitions.entered += 1;
+    }
+
+    fn count_exit(mut transitions: ResMut<Transitions>) {
+        transitions.exited += 1;
+    }
+
+    fn transition_stage() -> SystemStage {
+        SystemStage::single_threaded()
+            .with_system_set(State::<AppState>::get_driver())
+            .with_system_set(SystemSet::on_enter(AppState::InGame).with_system(count_enter))
+            .with_system_set(SystemSet::on_exit(AppState::InGame).with_system(count_exit))
+    }
+
+    fn setup_world() -> (World, SystemStage) {
+        let mut world = World::new();
+        world.insert_resource(State::new(AppState::InGame));
+        world.init_resource::<Transitions>();
+        let mut stage = transition_stage();
+        stage.run(&mut world);
+        *world.resource_mut::<Transitions>() = Transitions::default();
+        (world, stage)
+    }
+
+    #[test]
+    fn retry_reenters_in_game() {
+        let (mut world, mut stage) = setup_world();
+
+        retry(&mut world.resource_mut::<State<AppState>>()).unwrap();
+        stage.run(&mut world);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::InGame
+        );
+        let transitions = world.resource::<Transitions>();
+        assert_eq!((transitions.exited, transitions.entered), (1, 1));
+    }
+
+    #[test]
+    fn retry_from_pause_leaves_both_states() {
+        let (mut world, mut stage) = setup_world();
+        world
+            .resource_mut::<State<AppState>>()
+            .push(AppState::Paused)
+            .unwrap();
+        stage.run(&mut world);
+
+        retry(&mut world.resource_mut::<State<AppState>>()).unwrap();
+        stage.run(&mut world);
+        let state = world.resource::<State<AppState>>();
+        assert_eq!(*state.current(), AppState::InGame);
+        // Pausedの下に積まれていたInGameは残らない
+        assert_eq!(state.inactives(), &[]);
+        let transitions = world.resource::<Transitions>();
+        assert_eq!((transitions.exited, transitions.entered), (1, 1));
+    }
+
+    #[test]
+    fn retry_key_yields_to_queued_transition() {
+        let (mut world, _) = setup_world();
+        let mut input = Input::<InputAction>::default();
+        input.press(InputAction::Retry);
+        world.insert_resource(input);
+        world
+            .resource_mut::<State<AppState>>()
+            .set(AppState::GameOver)
+            .unwrap();
+
+        // 同じフレームでゲーム側の画面遷移が先に積まれていても落ちない
+        SystemStage::single_threaded()
+            .with_system(retry_by_keyboard_system)
+            .run(&mut world);
+        transition_stage().run(&mut world);
+        assert_eq!(
+            *world.resource::<State<AppState>>().current(),
+            AppState::GameOver
+        );
+    }
+}
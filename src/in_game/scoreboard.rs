@@ -1,9 +1,13 @@
 use crate::app_state::AppState;
+use crate::difficulty::Difficulties;
+use crate::high_score::{HighScores, ScoreCategory};
+use crate::practice::Practice;
 use crate::WINDOW_HEIGHT;
 use crate::WINDOW_WIDTH;
 use bevy::prelude::*;
 
-const DEFAULT_PLAYER_STOCK: i32 = 3;
+const MAX_PLAYER_BOMB: i32 = 5;
+pub const MAX_PLAYER_POWER: i32 = 3;
 
 pub struct ScoreBoardPlugin;
 
@@ -13,7 +17,10 @@ impl Plugin for ScoreBoardPlugin {
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
                     .with_system(display_score_system)
-                    .with_system(display_player_stock_system),
+                    .with_system(display_player_stock_system)
+                    .with_system(display_player_bomb_system)
+                    .with_system(display_player_power_system)
+                    .with_system(display_high_score_system),
             )
             .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cleanup));
     }
@@ -22,20 +29,41 @@ impl Plugin for ScoreBoardPlugin {
 pub struct Score {
     pub score: i128,
     player_stock: i32,
+    // コンティニュー時にここまで残機を戻す
+    initial_stock: i32,
+    player_bomb: i32,
+    // 難易度ごとのボムの初期数。復帰時にここまで補充する
+    initial_bomb: i32,
+    // パワーアイテムで増え、自機の弾の数が増える
+    player_power: i32,
+    // チェインによる倍率。全ての加点に掛かる
+    multiplier: f64,
+    // スコアによるエクステンドを行った回数
+    score_extend_count: usize,
+    continue_count: u32,
 }
 
-impl Default for Score {
-    fn default() -> Self {
+impl Score {
+    pub fn new(player_stock: i32, player_bomb: i32) -> Self {
         Score {
             score: 0,
-            player_stock: DEFAULT_PLAYER_STOCK,
+            player_stock,
+            initial_stock: player_stock,
+            player_bomb,
+            initial_bomb: player_bomb,
+            player_power: 0,
+            multiplier: 1.,
+            score_extend_count: 0,
+            continue_count: 0,
         }
     }
-}
 
-impl Score {
     pub fn add_score(&mut self, score_diff: u128) {
-        self.score += score_diff as i128;
+        self.score += (score_diff as f64 * self.multiplier).round() as i128;
+    }
+
+    pub fn set_multiplier(&mut self, multiplier: f64) {
+        self.multiplier = multiplier;
     }
 
     pub fn on_hit_enemy_bullet(&mut self) -> bool {
@@ -45,6 +73,75 @@ impl Score {
 
         self.player_stock > 0
     }
+
+    pub fn extend(&mut self, max_stock: i32) {
+        self.player_stock = (self.player_stock + 1).min(max_stock);
+    }
+
+    pub fn score_extend_count(&self) -> usize {
+        self.score_extend_count
+    }
+
+    pub fn count_score_extend(&mut self) {
+        self.score_extend_count += 1;
+    }
+
+    pub fn has_stock(&self) -> bool {
+        self.player_stock > 0
+    }
+
+    pub fn continue_count(&self) -> u32 {
+        self.continue_count
+    }
+
+    // コンティニューすると残機とボムが初期数に戻り、スコアは0からやり直しになる
+    pub fn continue_game(&mut self) {
+        self.score = 0;
+        self.score_extend_count = 0;
+        self.player_stock = self.initial_stock;
+        self.refill_bomb();
+        self.continue_count += 1;
+    }
+
+    pub fn use_bomb(&mut self) -> bool {
+        if self.player_bomb > 0 {
+            self.player_bomb -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // 復帰時にボムを補充する
+    pub fn refill_bomb(&mut self) {
+        self.player_bomb = self.player_bomb.max(self.initial_bomb);
+    }
+
+    pub fn add_bomb(&mut self) {
+        self.player_bomb = (self.player_bomb + 1).min(MAX_PLAYER_BOMB);
+    }
+
+    pub fn player_power(&self) -> i32 {
+        self.player_power
+    }
+
+    // 既に最大のときはfalseを返す
+    pub fn add_power(&mut self) -> bool {
+        if self.player_power >= MAX_PLAYER_POWER {
+            return false;
+        }
+        self.player_power += 1;
+        true
+    }
+
+    pub fn set_power(&mut self, power: i32) {
+        self.player_power = power.clamp(0, MAX_PLAYER_POWER);
+    }
+
+    // 被弾するとパワーが1段階下がる
+    pub fn lose_power(&mut self) {
+        self.player_power = (self.player_power - 1).max(0);
+    }
 }
 
 #[derive(Component)]
@@ -53,12 +150,35 @@ struct ScoreText;
 #[derive(Component)]
 struct PlayerStockText;
 
+#[derive(Component)]
+struct PlayerBombText;
+
+#[derive(Component)]
+struct PlayerPowerText;
+
+#[derive(Component)]
+struct HighScoreText;
+
 struct ScoreBoardEntities {
     entities: Vec<Entity>,
 }
 
-fn setup(mut commands: Commands, mut asset_server: ResMut<AssetServer>) {
-    commands.insert_resource(Score::default());
+fn setup(
+    mut commands: Commands,
+    mut asset_server: ResMut<AssetServer>,
+    difficulties: Res<Difficulties>,
+    practice: Option<Res<Practice>>,
+) {
+    let difficulty = difficulties.current();
+    let score = match practice {
+        Some(practice) => {
+            let mut score = Score::new(practice.player_stock, difficulty.player_bomb);
+            score.set_power(practice.player_power);
+            score
+        }
+        None => Score::new(difficulty.player_stock, difficulty.player_bomb),
+    };
+    commands.insert_resource(score);
     setup_score_area(&mut commands, &mut asset_server)
 }
 
@@ -75,6 +195,9 @@ fn setup_score_area(commands: &mut Commands, asset_server: &mut AssetServer) {
     ));
     score_board_entities.append(&mut spawn_score_texts(commands, asset_server));
     score_board_entities.append(&mut spawn_stock_texts(commands, asset_server));
+    score_board_entities.append(&mut spawn_bomb_texts(commands, asset_server));
+    score_board_entities.append(&mut spawn_power_texts(commands, asset_server));
+    score_board_entities.append(&mut spawn_high_score_texts(commands, asset_server));
 
     commands.insert_resource(ScoreBoardEntities {
         entities: score_board_entities,
@@ -100,6 +223,34 @@ fn display_player_stock_system(
     text.sections[0].value = format!("{}", score.player_stock);
 }
 
+fn display_player_bomb_system(
+    score: Res<Score>,
+    mut query: Query<&mut Text, With<PlayerBombText>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[0].value = format!("{}", score.player_bomb);
+}
+
+// プレイ中のスコアが記録を超えたらそちらを表示する
+fn display_high_score_system(
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    category: Res<ScoreCategory>,
+    mut query: Query<&mut Text, With<HighScoreText>>,
+) {
+    let mut text = query.single_mut();
+    let high_score = high_scores.top_score(&category).max(score.score);
+    text.sections[0].value = format!("{}", high_score);
+}
+
+fn display_player_power_system(
+    score: Res<Score>,
+    mut query: Query<&mut Text, With<PlayerPowerText>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[0].value = format!("{} / {}", score.player_power, MAX_PLAYER_POWER);
+}
+
 /*
  * Utility
  */
@@ -252,3 +403,222 @@ fn spawn_stock_texts(commands: &mut Commands, asset_server: &AssetServer) -> Vec
 
     result
 }
+
+fn spawn_bomb_texts(commands: &mut Commands, asset_server: &AssetServer) -> Vec<Entity> {
+    let mut result = vec![];
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "Bomb".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(204.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .id(),
+    );
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(228.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PlayerBombText)
+            .id(),
+    );
+
+    result
+}
+
+fn spawn_power_texts(commands: &mut Commands, asset_server: &AssetServer) -> Vec<Entity> {
+    let mut result = vec![];
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "Power".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(266.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .id(),
+    );
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(290.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(PlayerPowerText)
+            .id(),
+    );
+
+    result
+}
+
+fn spawn_high_score_texts(commands: &mut Commands, asset_server: &AssetServer) -> Vec<Entity> {
+    let mut result = vec![];
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "HiScore".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(390.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .id(),
+    );
+
+    result.push(
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "".to_string(),
+                        style: TextStyle {
+                            font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(1.0, 1.0, 1.0),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(414.),
+                        left: Val::Px(WINDOW_WIDTH / 3. * 2. + 50.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(HighScoreText)
+            .id(),
+    );
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continue_resets_score_and_restores_stock() {
+        let mut score = Score::new(2, 3);
+        score.add_score(5000);
+        score.count_score_extend();
+        score.on_hit_enemy_bullet();
+        score.on_hit_enemy_bullet();
+        score.use_bomb();
+        assert!(!score.has_stock());
+
+        score.continue_game();
+        assert_eq!(score.score, 0);
+        assert_eq!(score.score_extend_count(), 0);
+        assert_eq!(score.continue_count(), 1);
+        // 初期数まで戻るので、2回被弾するまで残機がある
+        assert!(score.on_hit_enemy_bullet());
+        assert!(score.has_stock());
+        score.on_hit_enemy_bullet();
+        assert!(!score.has_stock());
+        for _ in 0..3 {
+            assert!(score.use_bomb());
+        }
+        assert!(!score.use_bomb());
+    }
+}
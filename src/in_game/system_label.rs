@@ -2,4 +2,8 @@ use bevy::prelude::*;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 pub enum GameSystemLabel {
     GameFrameUpdate,
+    CollisionBroadphase,
+    PlayerMovement,
+    // ミスやボス撃破で画面遷移を積むシステム
+    StateTransition,
 }
@@ -0,0 +1,229 @@
+use crate::settings::{ControlMode, KeyBindings, Settings};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+// スティックをこれ以上倒したら移動として扱う
+const STICK_THRESHOLD: f32 = 0.5;
+
+// キーボードとゲームパッドの入力を、ゲーム中の操作(InputAction)に変換する
+// ゲーム中のシステムはInput<KeyCode>ではなくInput<InputAction>を読む
+pub struct InputActionPlugin;
+
+impl Plugin for InputActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Input<InputAction>>()
+            .init_resource::<GamepadBindings>()
+            .init_resource::<InjectedInput>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_input_action_system.after(InputSystem),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Shot,
+    // 押している間は低速で移動する
+    Focus,
+    Bomb,
+    Pause,
+    Retry,
+    // コンティニューの確認などで使う決定と取り消し。キーボードではEnterとEscに固定
+    Confirm,
+    Cancel,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 11] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Shot,
+        InputAction::Focus,
+        InputAction::Bomb,
+        InputAction::Pause,
+        InputAction::Retry,
+        InputAction::Confirm,
+        InputAction::Cancel,
+    ];
+
+    pub fn is_move(&self) -> bool {
+        matches!(
+            self,
+            InputAction::MoveUp
+                | InputAction::MoveDown
+                | InputAction::MoveLeft
+                | InputAction::MoveRight
+        )
+    }
+
+    // マウスで操作するときのボタン
+    fn mouse_button(&self) -> Option<MouseButton> {
+        match self {
+            InputAction::Shot => Some(MouseButton::Left),
+            InputAction::Bomb => Some(MouseButton::Right),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self, key_bindings: &KeyBindings) -> KeyCode {
+        match self {
+            InputAction::MoveUp => key_bindings.up,
+            InputAction::MoveDown => key_bindings.down,
+            InputAction::MoveLeft => key_bindings.left,
+            InputAction::MoveRight => key_bindings.right,
+            InputAction::Shot => key_bindings.shot,
+            InputAction::Focus => key_bindings.focus,
+            InputAction::Bomb => key_bindings.bomb,
+            InputAction::Pause => key_bindings.pause,
+            InputAction::Retry => key_bindings.retry,
+            InputAction::Confirm => KeyCode::Return,
+            InputAction::Cancel => KeyCode::Escape,
+        }
+    }
+}
+
+/*
+ * Resource
+ */
+// ゲームパッドのボタンの割り当て。アプリ側で上書きして調整できる
+// 移動は十字キーのほかに左スティックでもできる
+pub struct GamepadBindings {
+    pub up: GamepadButtonType,
+    pub down: GamepadButtonType,
+    pub left: GamepadButtonType,
+    pub right: GamepadButtonType,
+    pub shot: GamepadButtonType,
+    pub focus: GamepadButtonType,
+    pub bomb: GamepadButtonType,
+    pub pause: GamepadButtonType,
+    pub retry: GamepadButtonType,
+    pub confirm: GamepadButtonType,
+    pub cancel: GamepadButtonType,
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            up: GamepadButtonType::DPadUp,
+            down: GamepadButtonType::DPadDown,
+            left: GamepadButtonType::DPadLeft,
+            right: GamepadButtonType::DPadRight,
+            shot: GamepadButtonType::South,
+            focus: GamepadButtonType::RightTrigger,
+            bomb: GamepadButtonType::East,
+            pause: GamepadButtonType::Start,
+            retry: GamepadButtonType::Select,
+            confirm: GamepadButtonType::South,
+            cancel: GamepadButtonType::East,
+        }
+    }
+}
+
+impl GamepadBindings {
+    fn button(&self, action: InputAction) -> GamepadButtonType {
+        match action {
+            InputAction::MoveUp => self.up,
+            InputAction::MoveDown => self.down,
+            InputAction::MoveLeft => self.left,
+            InputAction::MoveRight => self.right,
+            InputAction::Shot => self.shot,
+            InputAction::Focus => self.focus,
+            InputAction::Bomb => self.bomb,
+            InputAction::Pause => self.pause,
+            InputAction::Retry => self.retry,
+            InputAction::Confirm => self.confirm,
+            InputAction::Cancel => self.cancel,
+        }
+    }
+}
+
+// リプレイやボットが押したことにする操作。キーボードやゲームパッドの入力と合わせて扱う
+#[derive(Default)]
+pub struct InjectedInput {
+    pressed: HashSet<InputAction>,
+}
+
+impl InjectedInput {
+    pub fn set_pressed(&mut self, action: InputAction, pressed: bool) {
+        if pressed {
+            self.pressed.insert(action);
+        } else {
+            self.pressed.remove(&action);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+/*
+ * System
+ */
+#[allow(clippy::too_many_arguments)]
+fn update_input_action_system(
+    settings: Res<Settings>,
+    gamepad_bindings: Res<GamepadBindings>,
+    injected: Res<InjectedInput>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    mut action_input: ResMut<Input<InputAction>>,
+) {
+    action_input.clear();
+
+    // 左スティックの傾き
+    let mut stick = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(*gamepad, axis_type))
+                .unwrap_or(0.)
+        };
+        stick += Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+    }
+
+    for action in InputAction::ALL {
+        let by_gamepad = gamepads.iter().any(|gamepad| {
+            gamepad_input.pressed(GamepadButton::new(
+                *gamepad,
+                gamepad_bindings.button(action),
+            ))
+        });
+        let by_stick = match action {
+            InputAction::MoveUp => stick.y > STICK_THRESHOLD,
+            InputAction::MoveDown => stick.y < -STICK_THRESHOLD,
+            InputAction::MoveLeft => stick.x < -STICK_THRESHOLD,
+            InputAction::MoveRight => stick.x > STICK_THRESHOLD,
+            _ => false,
+        };
+        let by_mouse = settings.control_mode == ControlMode::Pointer
+            && action
+                .mouse_button()
+                .map(|button| mouse_input.pressed(button))
+                .unwrap_or(false);
+        let pressed = keyboard_input.pressed(action.key(&settings.key_bindings))
+            || by_mouse
+            || by_gamepad
+            || by_stick
+            || injected.pressed.contains(&action);
+        if pressed {
+            action_input.press(action);
+        } else {
+            action_input.release(action);
+        }
+    }
+}
@@ -1,11 +1,23 @@
 mod app_state;
+mod barrage_preview;
+mod difficulty;
 mod ending;
 mod game_over;
+mod high_score;
 mod in_game;
+pub mod input_action;
 mod menu;
+mod menu_navigation;
+mod menu_widget;
+mod options;
+mod practice;
+mod replays;
+mod settings;
+mod storage;
 
 use app_state::AppState;
 use bevy::prelude::*;
+pub use in_game::{enemy::barrage_simulator, hitbox, play_area};
 
 pub struct GamePlugin;
 
@@ -25,10 +37,19 @@ impl Plugin for GamePlugin {
         .insert_resource(ClearColor(Color::rgb(0., 0., 0.)))
         .add_state(AppState::Menu)
         .add_plugins(DefaultPlugins)
+        .add_plugin(settings::SettingsPlugin)
+        .add_plugin(input_action::InputActionPlugin)
         .add_plugin(menu::MenuPlugin)
+        .add_plugin(menu_navigation::MenuNavigationPlugin)
         .add_plugin(in_game::InGamePlugin)
         .add_plugin(game_over::GameOverPlugin)
         .add_plugin(ending::EndingPlugin)
+        .add_plugin(barrage_preview::BarragePreviewPlugin)
+        .add_plugin(high_score::HighScorePlugin)
+        .add_plugin(difficulty::DifficultyPlugin)
+        .add_plugin(practice::PracticePlugin)
+        .add_plugin(replays::ReplaysPlugin)
+        .add_plugin(options::OptionsPlugin)
         .add_startup_system(setup_camera);
     }
 }
@@ -1,10 +1,14 @@
-use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy_sample_stg::GamePlugin;
 
 fn main() {
-    App::new()
-        .add_plugin(GamePlugin)
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
-        .run();
+    let mut app = App::new();
+    app.add_plugin(GamePlugin)
+        .add_plugin(FrameTimeDiagnosticsPlugin::default());
+    // 弾のプールなどの計測値をログに出す
+    if std::env::var_os("STG_DIAGNOSTICS").is_some() {
+        app.add_plugin(LogDiagnosticsPlugin::default());
+    }
+    app.run();
 }
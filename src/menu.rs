@@ -1,11 +1,11 @@
 use crate::app_state::AppState;
+use crate::difficulty::Difficulties;
+use crate::menu_navigation::{MenuChangeEvent, MenuSelectEvent};
+use crate::menu_widget::{changed_items, selected_items, set_menu_item_label, spawn_menu};
 
+use bevy::app::AppExit;
 use bevy::prelude::*;
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
-
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -25,14 +25,27 @@ struct TitleData {
 }
 
 struct MenuData {
-    button_entity: Entity,
+    menu_entity: Entity,
+}
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Start,
+    // 押すか左右で難易度を切り替える
+    Difficulty,
+    Practice,
+    BarragePreview,
+    Replays,
+    HighScores,
+    Options,
+    Quit,
 }
 
 fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
     let title_entity = commands
         .spawn_bundle(NodeBundle {
             style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(30.)),
+                size: Size::new(Val::Percent(100.0), Val::Percent(25.)),
                 flex_direction: FlexDirection::ColumnReverse,
                 justify_content: JustifyContent::Center,
                 position_type: PositionType::Absolute,
@@ -88,63 +101,87 @@ fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(TitleData { title_entity });
 }
 
-fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let button_entity = commands
-        .spawn_bundle(ButtonBundle {
-            style: Style {
-                size: Size::new(Val::Px(150.0), Val::Px(65.0)),
-                // center button
-                margin: UiRect::all(Val::Auto),
-                // horizontally center child text
-                justify_content: JustifyContent::Center,
-                // vertically center child text
-                align_items: AlignItems::Center,
+fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    difficulties: Res<Difficulties>,
+) {
+    let items = [
+        (MenuButton::Start, "Start".to_string()),
+        (MenuButton::Difficulty, difficulty_label(&difficulties)),
+        (MenuButton::Practice, "Practice".to_string()),
+        (MenuButton::BarragePreview, "Barrage Preview".to_string()),
+        (MenuButton::Replays, "Replays".to_string()),
+        (MenuButton::HighScores, "High Scores".to_string()),
+        (MenuButton::Options, "Options".to_string()),
+        (MenuButton::Quit, "Quit".to_string()),
+    ];
+    // タイトルの下に並べる
+    let menu_entity = spawn_menu(
+        &mut commands,
+        &asset_server,
+        Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(75.0)),
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Percent(25.0),
+                left: Val::Px(0.),
                 ..Default::default()
             },
-            color: NORMAL_BUTTON.into(),
             ..Default::default()
-        })
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::from_section(
-                    "Play",
-                    TextStyle {
-                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
-                        font_size: 40.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                ),
-                ..Default::default()
-            });
-        })
-        .id();
-    commands.insert_resource(MenuData { button_entity });
+        },
+        &items,
+    );
+    commands.insert_resource(MenuData { menu_entity });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn menu_system(
     mut state: ResMut<State<AppState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut UiColor),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut difficulties: ResMut<Difficulties>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut change_events: EventReader<MenuChangeEvent>,
+    mut app_exit_events: EventWriter<AppExit>,
+    button_query: Query<&MenuButton>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
 ) {
-    for (interaction, mut color) in interaction_query.iter_mut() {
-        match *interaction {
-            Interaction::Clicked => {
-                *color = PRESSED_BUTTON.into();
-                state.set(AppState::InGame).unwrap();
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+    for (entity, menu_button, diff) in changed_items(&mut change_events, &button_query) {
+        if let MenuButton::Difficulty = menu_button {
+            difficulties.cycle(diff);
+            let children = children_query.get(entity).unwrap();
+            set_menu_item_label(children, &mut text_query, &difficulty_label(&difficulties));
+        }
+    }
+    for (entity, menu_button) in selected_items(&mut select_events, &button_query) {
+        let next_state = match menu_button {
+            MenuButton::Start => AppState::InGame,
+            MenuButton::Difficulty => {
+                difficulties.cycle(1);
+                let children = children_query.get(entity).unwrap();
+                set_menu_item_label(children, &mut text_query, &difficulty_label(&difficulties));
+                continue;
             }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
+            MenuButton::Practice => AppState::PracticeMenu,
+            MenuButton::BarragePreview => AppState::BarragePreview,
+            MenuButton::Replays => AppState::Replays,
+            MenuButton::HighScores => AppState::HighScore,
+            MenuButton::Options => AppState::Options,
+            MenuButton::Quit => {
+                app_exit_events.send(AppExit);
+                continue;
             }
-        }
+        };
+        // 同じフレームで複数選ばれたときは先に受け付けた方を優先する
+        let _ = state.set(next_state);
     }
 }
 
 fn cleanup_menu(mut commands: Commands, title_data: Res<TitleData>, menu_data: Res<MenuData>) {
     commands.entity(title_data.title_entity).despawn_recursive();
-    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.menu_entity).despawn_recursive();
+}
+
+fn difficulty_label(difficulties: &Difficulties) -> String {
+    format!("< {} >", difficulties.current().name)
 }
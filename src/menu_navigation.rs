@@ -0,0 +1,332 @@
+use crate::input_action::InputAction;
+use bevy::prelude::*;
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const FOCUSED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+
+// 操作の割り当てに関わらず、メニューでいつも使えるキーとボタン
+const FIXED_KEYS: [(InputAction, KeyCode); 5] = [
+    (InputAction::MoveUp, KeyCode::Up),
+    (InputAction::MoveDown, KeyCode::Down),
+    (InputAction::MoveLeft, KeyCode::Left),
+    (InputAction::MoveRight, KeyCode::Right),
+    (InputAction::Confirm, KeyCode::Space),
+];
+const FIXED_BUTTONS: [(InputAction, GamepadButtonType); 4] = [
+    (InputAction::MoveUp, GamepadButtonType::DPadUp),
+    (InputAction::MoveDown, GamepadButtonType::DPadDown),
+    (InputAction::MoveLeft, GamepadButtonType::DPadLeft),
+    (InputAction::MoveRight, GamepadButtonType::DPadRight),
+];
+
+// メニューのボタンをマウスだけでなくキーボードやゲームパッドでも選べるようにする
+// 画面ごとのメニューはボタンが押されたことをMenuSelectEventで受け取る
+// キーボードとゲームパッドはInput<InputAction>の移動、決定、取り消しで読む
+pub struct MenuNavigationPlugin;
+
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuSelectEvent>()
+            .add_event::<MenuChangeEvent>()
+            .add_event::<MenuCancelEvent>()
+            .add_system(focus_first_system)
+            .add_system(mouse_navigation_system.after(focus_first_system))
+            .add_system(keyboard_navigation_system.after(mouse_navigation_system))
+            .add_system(highlight_system.after(keyboard_navigation_system));
+    }
+}
+
+/*
+ * Event
+ */
+// 選ばれたボタンのエンティティ
+pub struct MenuSelectEvent(pub Entity);
+
+// 選んでいるボタンで左右が押された。diffは左が-1、右が1
+pub struct MenuChangeEvent {
+    pub entity: Entity,
+    pub diff: isize,
+}
+
+// 取り消しが押された。前の画面に戻るのに使う
+pub struct MenuCancelEvent;
+
+/*
+ * Resource
+ */
+// 名前の入力中などに、キー入力でメニューが動かないようにする
+pub struct MenuNavigationLock;
+
+// 弾幕のプレビューのように移動の操作で自機を動かす画面では、メニューは矢印キーと十字キーだけで動かす
+pub struct MenuMoveActionLock;
+
+/*
+ * Component
+ */
+// キーで選べるボタン。orderの小さい順に上から並ぶ
+#[derive(Component)]
+pub struct Focusable {
+    pub order: usize,
+}
+
+#[derive(Component)]
+pub struct Focused;
+
+/*
+ * System
+ */
+// メニューが出たときは先頭のボタンを選んでおく
+fn focus_first_system(
+    mut commands: Commands,
+    focusable_query: Query<(Entity, &Focusable)>,
+    focused_query: Query<(), With<Focused>>,
+) {
+    if !focused_query.is_empty() {
+        return;
+    }
+    if let Some((entity, _)) = focusable_query
+        .iter()
+        .min_by_key(|(_, focusable)| focusable.order)
+    {
+        commands.entity(entity).insert(Focused);
+    }
+}
+
+fn mouse_navigation_system(
+    mut commands: Commands,
+    mut select_events: EventWriter<MenuSelectEvent>,
+    interaction_query: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    for (entity, interaction) in interaction_query.iter() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        move_focus(&mut commands, &focused_query, entity);
+        if *interaction == Interaction::Clicked {
+            select_events.send(MenuSelectEvent(entity));
+        }
+    }
+}
+
+// 決定したキーは、切り替わった先の画面で同じフレームに拾われないように消しておく
+#[allow(clippy::too_many_arguments)]
+fn keyboard_navigation_system(
+    mut commands: Commands,
+    lock: Option<Res<MenuNavigationLock>>,
+    move_action_lock: Option<Res<MenuMoveActionLock>>,
+    mut action_input: ResMut<Input<InputAction>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut gamepad_input: ResMut<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut select_events: EventWriter<MenuSelectEvent>,
+    mut change_events: EventWriter<MenuChangeEvent>,
+    mut cancel_events: EventWriter<MenuCancelEvent>,
+    focusable_query: Query<(Entity, &Focusable)>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    if lock.is_some() {
+        return;
+    }
+    let focused = match focused_query.iter().next() {
+        Some(focused) => focused,
+        None => return,
+    };
+
+    let use_action = |action: InputAction| move_action_lock.is_none() || !action.is_move();
+    let mut just_pressed = |action: InputAction| {
+        let mut pressed = use_action(action) && action_input.clear_just_pressed(action);
+        for (_, key) in FIXED_KEYS.iter().filter(|(a, _)| *a == action) {
+            pressed |= keyboard_input.clear_just_pressed(*key);
+        }
+        for (_, button_type) in FIXED_BUTTONS.iter().filter(|(a, _)| *a == action) {
+            for gamepad in gamepads.iter() {
+                pressed |=
+                    gamepad_input.clear_just_pressed(GamepadButton::new(*gamepad, *button_type));
+            }
+        }
+        pressed
+    };
+    let up = just_pressed(InputAction::MoveUp);
+    let down = just_pressed(InputAction::MoveDown);
+    let left = just_pressed(InputAction::MoveLeft);
+    let right = just_pressed(InputAction::MoveRight);
+    let select = just_pressed(InputAction::Confirm);
+    let cancel = just_pressed(InputAction::Cancel);
+
+    let diff = match (up, down) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+    if diff != 0 {
+        let mut entities: Vec<(Entity, usize)> = focusable_query
+            .iter()
+            .map(|(entity, focusable)| (entity, focusable.order))
+            .collect();
+        entities.sort_by_key(|(_, order)| *order);
+        if let Some(index) = entities.iter().position(|(entity, _)| *entity == focused) {
+            let len = entities.len() as isize;
+            let next = (index as isize + diff).rem_euclid(len) as usize;
+            move_focus(&mut commands, &focused_query, entities[next].0);
+        }
+    }
+    match (left, right) {
+        (true, false) => change_events.send(MenuChangeEvent {
+            entity: focused,
+            diff: -1,
+        }),
+        (false, true) => change_events.send(MenuChangeEvent {
+            entity: focused,
+            diff: 1,
+        }),
+        _ => {}
+    }
+    if select {
+        select_events.send(MenuSelectEvent(focused));
+    }
+    if cancel {
+        cancel_events.send(MenuCancelEvent);
+    }
+}
+
+fn highlight_system(
+    mut query: Query<(&Interaction, &mut UiColor, Option<&Focused>), With<Focusable>>,
+) {
+    for (interaction, mut color, focused) in query.iter_mut() {
+        let next_color = match (interaction, focused) {
+            (Interaction::Clicked, _) => PRESSED_BUTTON,
+            (_, Some(_)) => FOCUSED_BUTTON,
+            _ => NORMAL_BUTTON,
+        };
+        if color.0 != next_color {
+            color.0 = next_color;
+        }
+    }
+}
+
+/*
+ * Utils
+ */
+// 操作の割り当てに関わらず、メニューの選択と決定に使うキー
+pub fn is_menu_key(key: KeyCode) -> bool {
+    FIXED_KEYS.iter().any(|(_, k)| *k == key)
+}
+
+fn move_focus(commands: &mut Commands, focused_query: &Query<Entity, With<Focused>>, to: Entity) {
+    for entity in focused_query.iter() {
+        if entity != to {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+    commands.entity(to).insert(Focused);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+
+    fn setup_world() -> (World, Vec<Entity>) {
+        let mut world = World::new();
+        world.insert_resource(Input::<InputAction>::default());
+        world.insert_resource(Input::<KeyCode>::default());
+        world.insert_resource(Input::<GamepadButton>::default());
+        world.insert_resource(Gamepads::default());
+        world.init_resource::<Events<MenuSelectEvent>>();
+        world.init_resource::<Events<MenuChangeEvent>>();
+        world.init_resource::<Events<MenuCancelEvent>>();
+        let items: Vec<Entity> = (0..3)
+            .map(|order| world.spawn().insert(Focusable { order }).id())
+            .collect();
+        world.entity_mut(items[0]).insert(Focused);
+        (world, items)
+    }
+
+    fn press(world: &mut World, action: InputAction) {
+        let mut input = world.resource_mut::<Input<InputAction>>();
+        input.reset(action);
+        input.press(action);
+    }
+
+    fn run_navigation(world: &mut World) {
+        SystemStage::single_threaded()
+            .with_system(keyboard_navigation_system)
+            .run(world);
+    }
+
+    fn focused(world: &mut World) -> Vec<Entity> {
+        world
+            .query_filtered::<Entity, With<Focused>>()
+            .iter(world)
+            .collect()
+    }
+
+    #[test]
+    fn moves_focus_and_selects_by_actions() {
+        let (mut world, items) = setup_world();
+
+        // 先頭から上に動くと最後に回る
+        press(&mut world, InputAction::MoveUp);
+        run_navigation(&mut world);
+        assert_eq!(focused(&mut world), vec![items[2]]);
+
+        press(&mut world, InputAction::MoveRight);
+        press(&mut world, InputAction::Confirm);
+        press(&mut world, InputAction::Cancel);
+        run_navigation(&mut world);
+        let change_events = world.resource::<Events<MenuChangeEvent>>();
+        let change = change_events.iter_current_update_events().next().unwrap();
+        assert_eq!((change.entity, change.diff), (items[2], 1));
+        let select_events = world.resource::<Events<MenuSelectEvent>>();
+        assert_eq!(
+            select_events
+                .iter_current_update_events()
+                .map(|e| e.0)
+                .collect::<Vec<_>>(),
+            vec![items[2]]
+        );
+        assert_eq!(
+            world
+                .resource::<Events<MenuCancelEvent>>()
+                .iter_current_update_events()
+                .count(),
+            1
+        );
+        // 決定は次の画面で拾われないように消しておく
+        assert!(!world
+            .resource::<Input<InputAction>>()
+            .just_pressed(InputAction::Confirm));
+    }
+
+    #[test]
+    fn move_action_lock_keeps_arrow_keys() {
+        let (mut world, items) = setup_world();
+        world.insert_resource(MenuMoveActionLock);
+
+        press(&mut world, InputAction::MoveDown);
+        run_navigation(&mut world);
+        assert_eq!(focused(&mut world), vec![items[0]]);
+        // 自機の移動に使うので、移動の操作は残しておく
+        assert!(world
+            .resource::<Input<InputAction>>()
+            .just_pressed(InputAction::MoveDown));
+
+        world.resource_mut::<Input<KeyCode>>().press(KeyCode::Down);
+        run_navigation(&mut world);
+        assert_eq!(focused(&mut world), vec![items[1]]);
+    }
+
+    #[test]
+    fn lock_ignores_actions() {
+        let (mut world, items) = setup_world();
+        world.insert_resource(MenuNavigationLock);
+
+        press(&mut world, InputAction::MoveDown);
+        press(&mut world, InputAction::Confirm);
+        run_navigation(&mut world);
+        assert_eq!(focused(&mut world), vec![items[0]]);
+        assert!(world.resource::<Events<MenuSelectEvent>>().is_empty());
+    }
+}
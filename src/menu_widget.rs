@@ -0,0 +1,147 @@
+use crate::menu_navigation::{Focusable, MenuChangeEvent, MenuSelectEvent};
+use bevy::prelude::*;
+
+// ボタンの大きさ。項目の多い画面や、値を並べる画面で変える
+#[derive(Clone, Copy)]
+pub struct MenuItemSize {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+    pub font_size: f32,
+}
+
+impl Default for MenuItemSize {
+    fn default() -> Self {
+        Self {
+            width: 300.0,
+            height: 44.0,
+            margin: 5.0,
+            font_size: 28.0,
+        }
+    }
+}
+
+// 縦に並んだボタンのメニュー。項目ごとにTを付けておき、選ばれた項目をselected_itemsで受け取る
+// 並び順がそのままキーで選ぶ順になる
+pub fn spawn_menu<T: Component + Copy>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    style: Style,
+    items: &[(T, String)],
+) -> Entity {
+    spawn_sized_menu(
+        commands,
+        asset_server,
+        style,
+        MenuItemSize::default(),
+        items,
+    )
+}
+
+pub fn spawn_sized_menu<T: Component + Copy>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    style: Style,
+    size: MenuItemSize,
+    items: &[(T, String)],
+) -> Entity {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..style
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            for (order, (item, label)) in items.iter().enumerate() {
+                spawn_menu_item(parent, asset_server, size, *item, order, label);
+            }
+        })
+        .id()
+}
+
+// 画面の中央に置くメニュー
+pub fn centered_menu_style() -> Style {
+    Style {
+        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+        position_type: PositionType::Absolute,
+        ..Default::default()
+    }
+}
+
+// 選ばれた項目のエンティティとT
+pub fn selected_items<T: Component + Copy>(
+    select_events: &mut EventReader<MenuSelectEvent>,
+    item_query: &Query<&T>,
+) -> Vec<(Entity, T)> {
+    select_events
+        .iter()
+        .filter_map(|event| item_query.get(event.0).ok().map(|item| (event.0, *item)))
+        .collect()
+}
+
+// 左右が押された項目のエンティティとT、向き
+pub fn changed_items<T: Component + Copy>(
+    change_events: &mut EventReader<MenuChangeEvent>,
+    item_query: &Query<&T>,
+) -> Vec<(Entity, T, isize)> {
+    change_events
+        .iter()
+        .filter_map(|event| {
+            item_query
+                .get(event.entity)
+                .ok()
+                .map(|item| (event.entity, *item, event.diff))
+        })
+        .collect()
+}
+
+pub fn set_menu_item_label(children: &Children, text_query: &mut Query<&mut Text>, label: &str) {
+    for child in children.iter() {
+        if let Ok(mut text) = text_query.get_mut(*child) {
+            text.sections[0].value = label.to_string();
+        }
+    }
+}
+
+fn spawn_menu_item<T: Component>(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    size: MenuItemSize,
+    item: T,
+    order: usize,
+    label: &str,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(size.width), Val::Px(size.height)),
+                margin: UiRect::all(Val::Px(size.margin)),
+                // horizontally center child text
+                justify_content: JustifyContent::Center,
+                // vertically center child text
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(item)
+        .insert(Focusable { order })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                        font_size: size.font_size,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                ..Default::default()
+            });
+        });
+}
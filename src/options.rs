@@ -0,0 +1,481 @@
+use crate::app_state::AppState;
+use crate::input_action::InputAction;
+use crate::menu_navigation::{
+    is_menu_key, MenuCancelEvent, MenuChangeEvent, MenuNavigationLock, MenuSelectEvent,
+};
+use crate::menu_widget::{
+    changed_items, selected_items, set_menu_item_label, spawn_sized_menu, MenuItemSize,
+};
+use crate::settings::{
+    key_name, KeyBindings, Settings, BINDABLE_KEYS, MAX_VOLUME, MAX_WINDOW_SCALE, MIN_WINDOW_SCALE,
+    RESERVED_KEYS,
+};
+use bevy::prelude::*;
+
+const WINDOW_SCALE_STEP: f64 = 0.25;
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Options).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Options)
+                    .with_system(wait_key_system)
+                    .with_system(options_menu_system.after(wait_key_system))
+                    .with_system(display_options_system.after(options_menu_system)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Options).with_system(cleanup));
+    }
+}
+
+/*
+ * Resource
+ */
+// 値の行は左右で変え、選ぶと一つ進める。キーの行は選ぶと次に押すキーを割り当てる
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum OptionsRow {
+    ControlMode,
+    WindowScale,
+    Fullscreen,
+    MasterVolume,
+    BgmVolume,
+    SeVolume,
+    ShowHitbox,
+    Language,
+    KeyUp,
+    KeyDown,
+    KeyLeft,
+    KeyRight,
+    KeyShot,
+    KeyFocus,
+    KeyBomb,
+    KeyPause,
+    KeyRetry,
+    ResetToDefault,
+    Back,
+}
+
+impl OptionsRow {
+    const ALL: [OptionsRow; 19] = [
+        OptionsRow::ControlMode,
+        OptionsRow::WindowScale,
+        OptionsRow::Fullscreen,
+        OptionsRow::MasterVolume,
+        OptionsRow::BgmVolume,
+        OptionsRow::SeVolume,
+        OptionsRow::ShowHitbox,
+        OptionsRow::Language,
+        OptionsRow::KeyUp,
+        OptionsRow::KeyDown,
+        OptionsRow::KeyLeft,
+        OptionsRow::KeyRight,
+        OptionsRow::KeyShot,
+        OptionsRow::KeyFocus,
+        OptionsRow::KeyBomb,
+        OptionsRow::KeyPause,
+        OptionsRow::KeyRetry,
+        OptionsRow::ResetToDefault,
+        OptionsRow::Back,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            OptionsRow::ControlMode => "Control",
+            OptionsRow::WindowScale => "Window Scale",
+            OptionsRow::Fullscreen => "Fullscreen",
+            OptionsRow::MasterVolume => "Master Volume",
+            OptionsRow::BgmVolume => "BGM Volume",
+            OptionsRow::SeVolume => "SE Volume",
+            OptionsRow::ShowHitbox => "Show Hitbox",
+            OptionsRow::Language => "Language",
+            OptionsRow::KeyUp => "Key: Up",
+            OptionsRow::KeyDown => "Key: Down",
+            OptionsRow::KeyLeft => "Key: Left",
+            OptionsRow::KeyRight => "Key: Right",
+            OptionsRow::KeyShot => "Key: Shot",
+            OptionsRow::KeyFocus => "Key: Focus",
+            OptionsRow::KeyBomb => "Key: Bomb",
+            OptionsRow::KeyPause => "Key: Pause",
+            OptionsRow::KeyRetry => "Key: Retry",
+            OptionsRow::ResetToDefault => "Reset to default",
+            OptionsRow::Back => "Back",
+        }
+    }
+
+    fn key_binding_mut<'a>(&self, key_bindings: &'a mut KeyBindings) -> Option<&'a mut KeyCode> {
+        match self {
+            OptionsRow::KeyUp => Some(&mut key_bindings.up),
+            OptionsRow::KeyDown => Some(&mut key_bindings.down),
+            OptionsRow::KeyLeft => Some(&mut key_bindings.left),
+            OptionsRow::KeyRight => Some(&mut key_bindings.right),
+            OptionsRow::KeyShot => Some(&mut key_bindings.shot),
+            OptionsRow::KeyFocus => Some(&mut key_bindings.focus),
+            OptionsRow::KeyBomb => Some(&mut key_bindings.bomb),
+            OptionsRow::KeyPause => Some(&mut key_bindings.pause),
+            OptionsRow::KeyRetry => Some(&mut key_bindings.retry),
+            _ => None,
+        }
+    }
+
+    // ポーズ中はメニューと一緒に読まれるので、メニューのキーと重ねられない
+    fn is_read_in_menu(&self) -> bool {
+        matches!(self, OptionsRow::KeyPause | OptionsRow::KeyRetry)
+    }
+
+    fn is_key_binding(&self) -> bool {
+        matches!(
+            self,
+            OptionsRow::KeyUp
+                | OptionsRow::KeyDown
+                | OptionsRow::KeyLeft
+                | OptionsRow::KeyRight
+                | OptionsRow::KeyShot
+                | OptionsRow::KeyFocus
+                | OptionsRow::KeyBomb
+                | OptionsRow::KeyPause
+                | OptionsRow::KeyRetry
+        )
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let on_off = |b: bool| if b { "On" } else { "Off" }.to_string();
+        match self {
+            OptionsRow::ControlMode => settings.control_mode.name().to_string(),
+            OptionsRow::WindowScale => format!("x{:.2}", settings.window_scale),
+            OptionsRow::Fullscreen => on_off(settings.fullscreen),
+            OptionsRow::MasterVolume => settings.master_volume.to_string(),
+            OptionsRow::BgmVolume => settings.bgm_volume.to_string(),
+            OptionsRow::SeVolume => settings.se_volume.to_string(),
+            OptionsRow::ShowHitbox => on_off(settings.show_hitbox),
+            OptionsRow::Language => settings.language.name().to_string(),
+            OptionsRow::KeyUp => key_name(settings.key_bindings.up),
+            OptionsRow::KeyDown => key_name(settings.key_bindings.down),
+            OptionsRow::KeyLeft => key_name(settings.key_bindings.left),
+            OptionsRow::KeyRight => key_name(settings.key_bindings.right),
+            OptionsRow::KeyShot => key_name(settings.key_bindings.shot),
+            OptionsRow::KeyFocus => key_name(settings.key_bindings.focus),
+            OptionsRow::KeyBomb => key_name(settings.key_bindings.bomb),
+            OptionsRow::KeyPause => key_name(settings.key_bindings.pause),
+            OptionsRow::KeyRetry => key_name(settings.key_bindings.retry),
+            OptionsRow::ResetToDefault | OptionsRow::Back => "".to_string(),
+        }
+    }
+
+    fn change(&self, settings: &mut Settings, diff: isize) {
+        match self {
+            OptionsRow::ControlMode => settings.control_mode = settings.control_mode.next(),
+            OptionsRow::WindowScale => {
+                settings.window_scale = (settings.window_scale + WINDOW_SCALE_STEP * diff as f64)
+                    .clamp(MIN_WINDOW_SCALE, MAX_WINDOW_SCALE);
+            }
+            OptionsRow::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            OptionsRow::MasterVolume => change_volume(&mut settings.master_volume, diff),
+            OptionsRow::BgmVolume => change_volume(&mut settings.bgm_volume, diff),
+            OptionsRow::SeVolume => change_volume(&mut settings.se_volume, diff),
+            OptionsRow::ShowHitbox => settings.show_hitbox = !settings.show_hitbox,
+            OptionsRow::Language => settings.language = settings.language.next(),
+            _ => {}
+        }
+    }
+
+    fn item_label(&self, settings: &Settings, waiting_key: Option<OptionsRow>) -> String {
+        if waiting_key == Some(*self) {
+            return format!("{}: Press a key...", self.label());
+        }
+        match self {
+            OptionsRow::ResetToDefault | OptionsRow::Back => self.label().to_string(),
+            _ => format!("{}: {}", self.label(), self.value(settings)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct OptionsMenu {
+    // キーの割り当てを変えるために、次に押されるキーを待っている行
+    waiting_key: Option<OptionsRow>,
+    // 割り当てられなかったキーの案内
+    message: Option<String>,
+}
+
+/*
+ * Component
+ */
+#[derive(Component)]
+struct OptionsMessageText;
+
+struct OptionsData {
+    entity: Entity,
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    commands.insert_resource(OptionsMenu::default());
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+        font_size: 20.0,
+        color: Color::rgb(1., 1., 1.),
+    };
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .id();
+    let title_entity = commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section("OPTIONS", text_style.clone()),
+            ..Default::default()
+        })
+        .id();
+    let items: Vec<(OptionsRow, String)> = OptionsRow::ALL
+        .iter()
+        .map(|row| (*row, row.item_label(&settings, None)))
+        .collect();
+    // 項目が多いので、小さいボタンを並べる
+    let menu_entity = spawn_sized_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        MenuItemSize {
+            width: 420.0,
+            height: 26.0,
+            margin: 3.0,
+            font_size: 18.0,
+        },
+        &items,
+    );
+    let message_entity = commands
+        .spawn_bundle(TextBundle {
+            text: Text::from_section("", text_style),
+            ..Default::default()
+        })
+        .insert(OptionsMessageText)
+        .id();
+    commands
+        .entity(entity)
+        .push_children(&[title_entity, menu_entity, message_entity]);
+    commands.insert_resource(OptionsData { entity });
+}
+
+// 画面を抜けるときに設定を保存する
+fn cleanup(mut commands: Commands, data: Res<OptionsData>, settings: Res<Settings>) {
+    if let Err(e) = settings.save() {
+        warn!("Failed to save settings: {}", e);
+    }
+    commands.entity(data.entity).despawn_recursive();
+    commands.remove_resource::<OptionsData>();
+    commands.remove_resource::<OptionsMenu>();
+    commands.remove_resource::<MenuNavigationLock>();
+}
+
+// キーを待っている間はメニューを止めて、押されたキーをそのまま割り当てる
+fn wait_key_system(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut menu: ResMut<OptionsMenu>,
+    mut settings: ResMut<Settings>,
+) {
+    let row = match menu.waiting_key {
+        Some(row) => row,
+        None => return,
+    };
+    // 今と同じキーを押せば割り当ては変わらない
+    let pressed = BINDABLE_KEYS
+        .iter()
+        .chain(RESERVED_KEYS.iter())
+        .find(|key| keyboard_input.just_pressed(**key))
+        .copied();
+    if let Some(key) = pressed {
+        // 割り当てたキーで同じフレームにメニューが動かないように消しておく
+        keyboard_input.clear_just_pressed(key);
+        match rebind(&mut settings.key_bindings, row, key) {
+            Ok(()) => {
+                menu.waiting_key = None;
+                menu.message = None;
+                commands.remove_resource::<MenuNavigationLock>();
+            }
+            Err(e) => menu.message = Some(e.to_string()),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn options_menu_system(
+    mut commands: Commands,
+    mut menu: ResMut<OptionsMenu>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut change_events: EventReader<MenuChangeEvent>,
+    mut cancel_events: EventReader<MenuCancelEvent>,
+    row_query: Query<&OptionsRow>,
+) {
+    for (_, row, diff) in changed_items(&mut change_events, &row_query) {
+        row.change(&mut settings, diff);
+    }
+    for (_, row) in selected_items(&mut select_events, &row_query) {
+        if row.is_key_binding() {
+            menu.waiting_key = Some(row);
+            menu.message = None;
+            commands.insert_resource(MenuNavigationLock);
+            // 続けて選ばれた項目は受け付けない
+            break;
+        }
+        match row {
+            OptionsRow::ResetToDefault => *settings = Settings::default(),
+            OptionsRow::Back => {
+                let _ = state.set(AppState::Menu);
+            }
+            _ => row.change(&mut settings, 1),
+        }
+    }
+    if cancel_events.iter().count() > 0 {
+        let _ = state.set(AppState::Menu);
+    }
+}
+
+fn display_options_system(
+    menu: Res<OptionsMenu>,
+    settings: Res<Settings>,
+    row_query: Query<(&OptionsRow, &Children)>,
+    message_query: Query<Entity, With<OptionsMessageText>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !menu.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (row, children) in row_query.iter() {
+        set_menu_item_label(
+            children,
+            &mut text_query,
+            &row.item_label(&settings, menu.waiting_key),
+        );
+    }
+    let mut text = text_query.get_mut(message_query.single()).unwrap();
+    text.sections[0].value = menu.message.clone().unwrap_or_default();
+}
+
+/*
+ * Utils
+ */
+fn change_volume(volume: &mut u32, diff: isize) {
+    *volume = (*volume as isize + diff).clamp(0, MAX_VOLUME as isize) as u32;
+}
+
+// 他の操作に割り当て済みのキーなら、その操作には今のキーを割り当てて入れ替える
+fn rebind(
+    key_bindings: &mut KeyBindings,
+    row: OptionsRow,
+    key: KeyCode,
+) -> Result<(), anyhow::Error> {
+    check_key(key_bindings, row, key)?;
+    let current = *row.key_binding_mut(key_bindings).unwrap();
+    if let Some(other) = OptionsRow::ALL.iter().find(|other| {
+        **other != row && other.key_binding_mut(key_bindings).map(|k| *k) == Some(key)
+    }) {
+        if check_key(key_bindings, *other, current).is_err() {
+            return Err(anyhow::anyhow!(
+                "{} is already used by {}",
+                key_name(key),
+                other.label()
+            ));
+        }
+        *other.key_binding_mut(key_bindings).unwrap() = current;
+    }
+    *row.key_binding_mut(key_bindings).unwrap() = key;
+
+    Ok(())
+}
+
+fn check_key(
+    key_bindings: &KeyBindings,
+    row: OptionsRow,
+    key: KeyCode,
+) -> Result<(), anyhow::Error> {
+    if RESERVED_KEYS.contains(&key) {
+        return Err(anyhow::anyhow!("{} is used by menus", key_name(key)));
+    }
+    // 取り消しのキーはメニューで戻るのに使うので、意味の重なるポーズにだけ割り当てられる
+    if key == InputAction::Cancel.key(key_bindings) && row != OptionsRow::KeyPause {
+        return Err(anyhow::anyhow!(
+            "{} is used to go back in menus",
+            key_name(key)
+        ));
+    }
+    if row.is_read_in_menu() && is_menu_key(key) {
+        return Err(anyhow::anyhow!(
+            "{} is used by the pause menu",
+            key_name(key)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_volume_within_range() {
+        let mut settings = Settings::default();
+        OptionsRow::BgmVolume.change(&mut settings, 1);
+        assert_eq!(settings.bgm_volume, MAX_VOLUME);
+        OptionsRow::BgmVolume.change(&mut settings, -1);
+        assert_eq!(settings.bgm_volume, MAX_VOLUME - 1);
+        assert_eq!(settings.master_volume, MAX_VOLUME);
+
+        OptionsRow::Language.change(&mut settings, -1);
+        assert_eq!(settings.language.name(), "Japanese");
+    }
+
+    #[test]
+    fn swaps_key_already_bound_to_another_action() {
+        let mut key_bindings = KeyBindings::default();
+        rebind(&mut key_bindings, OptionsRow::KeyBomb, KeyCode::Space).unwrap();
+
+        assert_eq!(key_bindings.bomb, KeyCode::Space);
+        assert_eq!(key_bindings.shot, KeyCode::X);
+    }
+
+    #[test]
+    fn binds_escape_only_to_pause() {
+        let mut key_bindings = KeyBindings::default();
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyBomb, KeyCode::Escape).is_err());
+        // 入れ替えでボムがEscになる場合も断る
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyPause, KeyCode::X).is_err());
+        assert_eq!(key_bindings.bomb, KeyCode::X);
+        assert_eq!(key_bindings.pause, KeyCode::Escape);
+
+        rebind(&mut key_bindings, OptionsRow::KeyPause, KeyCode::P).unwrap();
+        assert_eq!(key_bindings.pause, KeyCode::P);
+        rebind(&mut key_bindings, OptionsRow::KeyPause, KeyCode::Escape).unwrap();
+        assert_eq!(key_bindings.pause, KeyCode::Escape);
+    }
+
+    #[test]
+    fn rejects_keys_used_by_menus() {
+        let mut key_bindings = KeyBindings::default();
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyShot, KeyCode::Return).is_err());
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyUp, KeyCode::Up).is_err());
+        // ポーズやリトライのキーはポーズメニューの選択と重ねられない
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyPause, KeyCode::Space).is_err());
+        // 入れ替えでポーズのキーがメニューのキーになる場合も断る
+        assert!(rebind(&mut key_bindings, OptionsRow::KeyShot, KeyCode::Escape).is_err());
+
+        assert_eq!(key_bindings.shot, KeyCode::Space);
+        assert_eq!(key_bindings.up, KeyCode::W);
+        assert_eq!(key_bindings.pause, KeyCode::Escape);
+    }
+}
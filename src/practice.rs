@@ -0,0 +1,372 @@
+use crate::app_state::AppState;
+use crate::difficulty::Difficulties;
+use crate::in_game::enemy::boss_phase_starts;
+use crate::in_game::scoreboard::MAX_PLAYER_POWER;
+use crate::menu_navigation::{MenuCancelEvent, MenuChangeEvent, MenuSelectEvent};
+use crate::menu_widget::{
+    changed_items, selected_items, set_menu_item_label, spawn_sized_menu, MenuItemSize,
+};
+use bevy::prelude::*;
+use std::fs::File;
+use std::path;
+
+const FRAME_STEP: i128 = 10;
+const MIN_PLAYER_STOCK: i32 = 1;
+const MAX_PLAYER_STOCK: i32 = 9;
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::PracticeMenu).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::PracticeMenu)
+                    .with_system(practice_menu_system)
+                    .with_system(display_practice_menu_system.after(practice_menu_system)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::PracticeMenu).with_system(cleanup))
+            // タイトルに戻ったら通常のプレイに戻す
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(clear_practice));
+    }
+}
+
+/*
+ * Resource
+ */
+// 練習モードでプレイ中の設定。このリソースがある間のスコアはハイスコアに記録しない
+pub struct Practice {
+    pub start_frame: i128,
+    // ボスを途中の形態から始めるときのLifeCount
+    pub boss_life_count: Option<i128>,
+    pub player_stock: i32,
+    pub player_power: i32,
+}
+
+struct PracticeStart {
+    name: String,
+    frame: i128,
+    boss_life_count: Option<i128>,
+}
+
+// 値の行は左右で変え、選ぶと一つ進める
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum PracticeMenuRow {
+    Section,
+    Frame,
+    Stock,
+    Power,
+    Start,
+    Back,
+}
+
+struct PracticeMenu {
+    starts: Vec<PracticeStart>,
+    // Noneはフレームを直接指定したとき
+    selected: Option<usize>,
+    frame: i128,
+    player_stock: i32,
+    player_power: i32,
+}
+
+impl PracticeMenu {
+    fn new(starts: Vec<PracticeStart>, player_stock: i32) -> Self {
+        Self {
+            // 始める場所が無ければフレームの直接指定にする
+            selected: if starts.is_empty() { None } else { Some(0) },
+            starts,
+            frame: 0,
+            player_stock,
+            player_power: 0,
+        }
+    }
+
+    fn change(&mut self, row: PracticeMenuRow, diff: isize) {
+        match row {
+            PracticeMenuRow::Section => {
+                if self.starts.is_empty() {
+                    return;
+                }
+                let len = self.starts.len() as isize;
+                let index = match self.selected {
+                    Some(index) => (index as isize + diff).rem_euclid(len) as usize,
+                    None => 0,
+                };
+                self.selected = Some(index);
+                self.frame = self.starts[index].frame;
+            }
+            PracticeMenuRow::Frame => {
+                self.selected = None;
+                self.frame = (self.frame + FRAME_STEP * diff as i128).clamp(0, self.max_frame());
+            }
+            PracticeMenuRow::Stock => {
+                self.player_stock =
+                    (self.player_stock + diff as i32).clamp(MIN_PLAYER_STOCK, MAX_PLAYER_STOCK);
+            }
+            PracticeMenuRow::Power => {
+                self.player_power = (self.player_power + diff as i32).clamp(0, MAX_PLAYER_POWER);
+            }
+            PracticeMenuRow::Start | PracticeMenuRow::Back => {}
+        }
+    }
+
+    fn label(&self, row: PracticeMenuRow) -> String {
+        match row {
+            PracticeMenuRow::Section => format!("< {} >", self.start_name()),
+            PracticeMenuRow::Frame => format!("Frame: < {} >", self.frame),
+            PracticeMenuRow::Stock => format!("Stock: < {} >", self.player_stock),
+            PracticeMenuRow::Power => format!("Power: < {} >", self.player_power),
+            PracticeMenuRow::Start => "Start".to_string(),
+            PracticeMenuRow::Back => "Back".to_string(),
+        }
+    }
+
+    fn max_frame(&self) -> i128 {
+        self.starts.iter().map(|s| s.frame).max().unwrap_or(0)
+    }
+
+    fn start_name(&self) -> &str {
+        match self.selected {
+            Some(index) => &self.starts[index].name,
+            None => "Custom",
+        }
+    }
+
+    fn practice(&self) -> Practice {
+        Practice {
+            start_frame: self.frame,
+            boss_life_count: self.selected.and_then(|i| self.starts[i].boss_life_count),
+            player_stock: self.player_stock,
+            player_power: self.player_power,
+        }
+    }
+}
+
+struct PracticeMenuData {
+    entity: Entity,
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, difficulties: Res<Difficulties>) {
+    // 区間を読み込めなくても、フレームの直接指定とボスからは始められる
+    let mut starts = load_sections("data/stage/sections.csv").unwrap_or_else(|e| {
+        warn!("Failed to load stage sections: {}", e);
+        vec![]
+    });
+    for (i, (frame, barrage_name, life_count)) in boss_phase_starts().into_iter().enumerate() {
+        starts.push(PracticeStart {
+            name: format!("Boss {} ({})", i + 1, barrage_name),
+            frame,
+            boss_life_count: Some(life_count),
+        });
+    }
+    let menu = PracticeMenu::new(starts, difficulties.current().player_stock);
+
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    format!(
+                        "PRACTICE\nDifficulty: {}\nScores are not recorded",
+                        difficulties.current().name
+                    ),
+                    TextStyle {
+                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                style: Style {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        })
+        .id();
+    let rows = [
+        PracticeMenuRow::Section,
+        PracticeMenuRow::Frame,
+        PracticeMenuRow::Stock,
+        PracticeMenuRow::Power,
+        PracticeMenuRow::Start,
+        PracticeMenuRow::Back,
+    ];
+    let items: Vec<(PracticeMenuRow, String)> =
+        rows.iter().map(|row| (*row, menu.label(*row))).collect();
+    // 区間の名前が収まるように幅を広げる
+    let menu_entity = spawn_sized_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        MenuItemSize {
+            width: 560.0,
+            font_size: 24.0,
+            ..Default::default()
+        },
+        &items,
+    );
+    commands.entity(entity).add_child(menu_entity);
+    commands.insert_resource(menu);
+    commands.insert_resource(PracticeMenuData { entity });
+}
+
+fn cleanup(mut commands: Commands, data: Res<PracticeMenuData>) {
+    commands.entity(data.entity).despawn_recursive();
+    commands.remove_resource::<PracticeMenuData>();
+    commands.remove_resource::<PracticeMenu>();
+}
+
+fn clear_practice(mut commands: Commands) {
+    commands.remove_resource::<Practice>();
+}
+
+fn practice_menu_system(
+    mut commands: Commands,
+    mut menu: ResMut<PracticeMenu>,
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut change_events: EventReader<MenuChangeEvent>,
+    mut cancel_events: EventReader<MenuCancelEvent>,
+    row_query: Query<&PracticeMenuRow>,
+) {
+    for (_, row, diff) in changed_items(&mut change_events, &row_query) {
+        menu.change(row, diff);
+    }
+    for (_, row) in selected_items(&mut select_events, &row_query) {
+        // 同じフレームで複数選ばれたときは先に受け付けた方を優先する
+        match row {
+            PracticeMenuRow::Start => {
+                if state.set(AppState::InGame).is_ok() {
+                    commands.insert_resource(menu.practice());
+                }
+            }
+            PracticeMenuRow::Back => {
+                let _ = state.set(AppState::Menu);
+            }
+            _ => menu.change(row, 1),
+        }
+    }
+    if cancel_events.iter().count() > 0 {
+        let _ = state.set(AppState::Menu);
+    }
+}
+
+fn display_practice_menu_system(
+    menu: Res<PracticeMenu>,
+    row_query: Query<(&PracticeMenuRow, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+    // 区間とフレームは互いに変わるので、まとめて書き直す
+    for (row, children) in row_query.iter() {
+        set_menu_item_label(children, &mut text_query, &menu.label(*row));
+    }
+}
+
+/*
+ * Utils
+ */
+// ステージの区間(名前, 開始フレーム)
+fn load_sections<P: AsRef<path::Path>>(file_path: P) -> Result<Vec<PracticeStart>, anyhow::Error> {
+    let file = File::open(file_path)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(file);
+    let mut sections = vec![];
+    for result in rdr.records() {
+        let record = result?;
+        sections.push(PracticeStart {
+            name: record[0].to_string(),
+            frame: record[1].parse::<i128>()?,
+            boss_life_count: None,
+        });
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn starts() -> Vec<PracticeStart> {
+        vec![
+            PracticeStart {
+                name: "Stage".to_string(),
+                frame: 0,
+                boss_life_count: None,
+            },
+            PracticeStart {
+                name: "Boss 2".to_string(),
+                frame: 500,
+                boss_life_count: Some(40),
+            },
+        ]
+    }
+
+    #[test]
+    fn loads_sections() {
+        let sections = load_sections("data/stage/sections.csv").unwrap();
+        assert!(!sections.is_empty());
+        assert!(sections.iter().all(|s| s.boss_life_count.is_none()));
+
+        let path = std::env::temp_dir().join("practice_invalid_sections.csv");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "name,frame\nStage,abc").unwrap();
+        assert!(load_sections(&path).is_err());
+        assert!(load_sections("data/stage/missing_sections.csv").is_err());
+    }
+
+    #[test]
+    fn selected_start_sets_frame_and_boss_life() {
+        let mut menu = PracticeMenu::new(starts(), 3);
+        menu.change(PracticeMenuRow::Section, 1);
+        let practice = menu.practice();
+        assert_eq!(practice.start_frame, 500);
+        assert_eq!(practice.boss_life_count, Some(40));
+
+        // 最後の区間から進めると最初の区間に戻る
+        menu.change(PracticeMenuRow::Section, 1);
+        assert_eq!(menu.start_name(), "Stage");
+        assert_eq!(menu.practice().boss_life_count, None);
+    }
+
+    #[test]
+    fn custom_frame_drops_boss_life() {
+        let mut menu = PracticeMenu::new(starts(), 3);
+        menu.change(PracticeMenuRow::Section, 1);
+        menu.change(PracticeMenuRow::Frame, 1);
+        assert_eq!(menu.start_name(), "Custom");
+        let practice = menu.practice();
+        // 最後の区間より後には進めない
+        assert_eq!(practice.start_frame, 500);
+        assert_eq!(practice.boss_life_count, None);
+
+        menu.change(PracticeMenuRow::Frame, -1);
+        assert_eq!(menu.practice().start_frame, 500 - FRAME_STEP);
+    }
+
+    #[test]
+    fn menu_without_starts_uses_custom_frame() {
+        let mut menu = PracticeMenu::new(vec![], 3);
+        assert_eq!(menu.start_name(), "Custom");
+        menu.change(PracticeMenuRow::Section, 1);
+        assert_eq!(menu.practice().start_frame, 0);
+    }
+}
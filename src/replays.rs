@@ -0,0 +1,88 @@
+use crate::app_state::AppState;
+use crate::menu_navigation::{MenuCancelEvent, MenuSelectEvent};
+use crate::menu_widget::{selected_items, spawn_menu};
+use bevy::prelude::*;
+
+// リプレイの記録はまだ無いので、今は案内を出すだけ
+pub struct ReplaysPlugin;
+
+impl Plugin for ReplaysPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Replays).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Replays).with_system(replays_menu_system),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Replays).with_system(cleanup));
+    }
+}
+
+struct ScreenData {
+    entity: Entity,
+}
+
+#[derive(Component, Clone, Copy)]
+enum ReplaysMenuItem {
+    Back,
+}
+
+/*
+ * System
+ */
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::from_section(
+                    "REPLAYS\n\nReplay recording is not implemented yet.",
+                    TextStyle {
+                        font: asset_server.load("fonts/x8y12pxTheStrongGamer.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                style: Style {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        })
+        .id();
+    let menu_entity = spawn_menu(
+        &mut commands,
+        &asset_server,
+        Style::default(),
+        &[(ReplaysMenuItem::Back, "Back".to_string())],
+    );
+    commands.entity(entity).add_child(menu_entity);
+    commands.insert_resource(ScreenData { entity });
+}
+
+fn cleanup(mut commands: Commands, data: Res<ScreenData>) {
+    commands.entity(data.entity).despawn_recursive();
+    commands.remove_resource::<ScreenData>();
+}
+
+fn replays_menu_system(
+    mut state: ResMut<State<AppState>>,
+    mut select_events: EventReader<MenuSelectEvent>,
+    mut cancel_events: EventReader<MenuCancelEvent>,
+    item_query: Query<&ReplaysMenuItem>,
+) {
+    let back = !selected_items(&mut select_events, &item_query).is_empty();
+    if back || cancel_events.iter().count() > 0 {
+        let _ = state.set(AppState::Menu);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::storage;
+use bevy::prelude::*;
+use bevy::window::WindowMode;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.json";
+pub const MIN_WINDOW_SCALE: f64 = 0.5;
+pub const MAX_WINDOW_SCALE: f64 = 2.0;
+pub const MAX_VOLUME: u32 = 10;
+
+// メニューの決定と選択にいつも使うので、操作には割り当てられないキー
+pub const RESERVED_KEYS: [KeyCode; 5] = [
+    KeyCode::Return,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+];
+
+// キーに割り当てられるキー。設定ファイルにはDebugの表記で保存する
+pub const BINDABLE_KEYS: [KeyCode; 51] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Space,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Back,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LControl,
+    KeyCode::RControl,
+    KeyCode::LAlt,
+    KeyCode::RAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Apostrophe,
+];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load().unwrap_or_else(|e| {
+            warn!("Failed to load settings: {}", e);
+            Settings::default()
+        });
+        app.insert_resource(settings)
+            .add_system(apply_window_settings_system);
+    }
+}
+
+/*
+ * Resource
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub control_mode: ControlMode,
+    pub key_bindings: KeyBindings,
+    // 画面の大きさの倍率。ゲーム内の座標は変わらない
+    pub window_scale: f64,
+    pub fullscreen: bool,
+    // 音量は0からMAX_VOLUMEまで。音はまだ鳴らないので、保存だけしておく
+    pub master_volume: u32,
+    pub bgm_volume: u32,
+    pub se_volume: u32,
+    // 自機の当たり判定を表示する
+    pub show_hitbox: bool,
+    // 表示する言語。まだ英語の表示しかないので、保存だけしておく
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            control_mode: ControlMode::Keyboard,
+            key_bindings: KeyBindings::default(),
+            window_scale: 1.0,
+            fullscreen: false,
+            master_volume: MAX_VOLUME,
+            bgm_volume: MAX_VOLUME,
+            se_volume: MAX_VOLUME,
+            show_hitbox: false,
+            language: Language::English,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Result<Self, anyhow::Error> {
+        storage::load_json(&storage::config_dir(), SETTINGS_FILE)
+    }
+
+    pub fn save(&self) -> Result<(), anyhow::Error> {
+        storage::save_json(&storage::config_dir(), SETTINGS_FILE, self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    #[serde(with = "key_code_serde")]
+    pub up: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub down: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub left: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub right: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub shot: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub focus: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub bomb: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub pause: KeyCode,
+    #[serde(with = "key_code_serde")]
+    pub retry: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::W,
+            down: KeyCode::S,
+            left: KeyCode::A,
+            right: KeyCode::D,
+            shot: KeyCode::Space,
+            focus: KeyCode::LShift,
+            bomb: KeyCode::X,
+            pause: KeyCode::Escape,
+            retry: KeyCode::R,
+        }
+    }
+}
+
+// 自機の操作方法。Pointerではマウスの位置に向かって動き、ボタンを押している間は連射する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlMode {
+    Keyboard,
+    Pointer,
+}
+
+impl ControlMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlMode::Keyboard => "Keyboard",
+            ControlMode::Pointer => "Mouse",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ControlMode::Keyboard => ControlMode::Pointer,
+            ControlMode::Pointer => ControlMode::Keyboard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    Japanese,
+}
+
+impl Language {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Japanese => "Japanese",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Language::English => Language::Japanese,
+            Language::Japanese => Language::English,
+        }
+    }
+}
+
+/*
+ * System
+ */
+// 起動時と設定が変わったときに、ウィンドウの大きさとフルスクリーンを反映する
+fn apply_window_settings_system(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    mut applied: Local<bool>,
+) {
+    if *applied && !settings.is_changed() {
+        return;
+    }
+    // ウィンドウができるまでは反映を待つ
+    if let Some(window) = windows.get_primary_mut() {
+        *applied = true;
+        window.set_scale_factor_override(Some(settings.window_scale));
+        window.set_mode(if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        });
+    }
+}
+
+/*
+ * Utils
+ */
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+mod key_code_serde {
+    use super::BINDABLE_KEYS;
+    use bevy::prelude::KeyCode;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &KeyCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::key_name(*key))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KeyCode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        BINDABLE_KEYS
+            .iter()
+            .find(|key| super::key_name(**key) == name)
+            .copied()
+            .ok_or_else(|| D::Error::custom(format!("Unknown key: {}", name)))
+    }
+}
@@ -0,0 +1,73 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SAVE_DIR_NAME: &str = "bevy_sample_stg";
+
+// ハイスコアを保存するディレクトリ。STG_SAVE_DIRで変更できる
+pub fn save_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("STG_SAVE_DIR") {
+        return PathBuf::from(dir);
+    }
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+    match base {
+        Some(base) => base.join(SAVE_DIR_NAME),
+        None => PathBuf::from("save"),
+    }
+}
+
+// 設定を保存するディレクトリ。STG_CONFIG_DIRで変更できる
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("STG_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    match base {
+        Some(base) => base.join(SAVE_DIR_NAME),
+        None => PathBuf::from("config"),
+    }
+}
+
+// ファイルが無ければ初期値を返す
+pub fn load_json<T: DeserializeOwned + Default>(
+    dir: &Path,
+    file_name: &str,
+) -> Result<T, anyhow::Error> {
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+pub fn save_json<T: Serialize>(
+    dir: &Path,
+    file_name: &str,
+    value: &T,
+) -> Result<(), anyhow::Error> {
+    fs::create_dir_all(dir)?;
+    // 書き込み途中で落ちても元のファイルが壊れないように、書き終えてから置き換える
+    let path = dir.join(file_name);
+    let tmp_path = dir.join(format!("{}.tmp", file_name));
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writer.flush()?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}